@@ -1,85 +1,76 @@
 pub extern crate chess_engine;
 use std::{
+    fs,
     io::{self, BufRead},
 };
 
-fn print_game_info(game: &mut chess_engine::chess_game::Game) {
-    game.print_board();
-    if game.game_is_over() {
-        let winner = game.get_winner();
-        println!("Game is over!");
-        if winner == None {
-            println!("Draw");
-        }
-        else if winner.unwrap() == ChessPieceColor::White {
-            println!("White wins!");
-        }
-        else if winner.unwrap() == ChessPieceColor::Black {
-            println!("Black wins!");
-        }
-        return;
-    }
-    use chess_engine::chess_game::*;
-    if game.turn as u32 == ChessPieceColor::Black as u32 {
-        println!("Black's turn");
-    } else {
-        println!("White's turn");
-    }
-    let check = game.is_check();
-    if check.is_some() {
-        println!("Check!");
+use chess_engine::chess_game::{ChessPieceColor, CommandSession, Game};
+
+fn parse_color(text: &str) -> Option<ChessPieceColor> {
+    match text {
+        "white" => return Some(ChessPieceColor::White),
+        "black" => return Some(ChessPieceColor::Black),
+        _ => return None,
     }
-    println!();
-    println!("Algebraic notation, ex Na3 to move knight to a3");
-    println!("Algebraic notation, specifying move exactly ex. Nb1b3 will show debug messages");
-    println!("Or, print possible moves: ex 'moves a2' to print all moves for a2");
+}
+
+fn print_usage() {
+    println!("Usage: main [--computer white|black] [--side white|black] [--script FILE]");
+    println!("  --computer  Let the computer play a side, the board is shown from the other side");
+    println!("  --side      Always show the board from one side, instead of the side to move");
+    println!("  --script    Run the commands in a file and print the output without colors");
 }
 
 fn main() {
-    let mut game = chess_engine::chess_game::Game::new();
+    let mut game = Game::new();
     game.set_up_board();
-    loop {
-        let stdin = io::stdin();
-        print_game_info(&mut game);
-        for line in stdin.lock().lines().map(|l| l.unwrap()) {
-            let user_input: Vec<String> =
-                line.split_whitespace().map(|num| num.to_string()).collect();
-            if user_input.len() == 1 {
-                // Treat it as a algebraic move
-                let result = game.algebraic_notation_move(user_input[0].clone());
-                if result.is_ok() {
-                    println!("Move Succesfull!");
-                } else {
-                    println!("Move Failed!");
-                    let error_message = result.err().unwrap();
-                    println!("{}", error_message);
-                }
-                print_game_info(&mut game);
+    let mut session = CommandSession::new(game);
+    let mut script = None;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for option in args.chunks(2) {
+        let value = option.get(1).map(|text| text.as_str()).unwrap_or("");
+        match (option[0].as_str(), parse_color(value)) {
+            ("--computer", Some(color)) => {
+                session.computer = Some(color);
+                // Show the board from the human's side
+                session.orientation = Some(match color {
+                    ChessPieceColor::White => ChessPieceColor::Black,
+                    ChessPieceColor::Black => ChessPieceColor::White,
+                });
             }
-            else if user_input.len() == 2 {
-                // Print all possible moves for square
-                if user_input[0] == "moves" {
-                    let position = user_input[1].clone();
-                    if position.len() != 2 {
-                        println!("Invalid input");
-                    }
-                    let char_vec: Vec<char> = position.chars().collect();
-                    let result_letter = chess_engine::chess_game::BoardPosition::get_coordinate_from_letter(char_vec[0]);
-                    let result_number = chess_engine::chess_game::BoardPosition::get_coordinate_from_number(char_vec[1]);
-                    if result_letter.is_ok() && result_number.is_ok() {
-                        let pos = chess_engine::chess_game::BoardPosition::new(result_letter.unwrap(), result_number.unwrap());
-                        game.print_board_with_possible_moves(Some(pos));
-                    }
-                    else {
-                        println!("Invalid input");
-                    }
-                }
-                else {
-                    println!("Invalid input");
-                }
-            } else {
-                println!("Invalid input");
+            ("--side", Some(color)) => session.orientation = Some(color),
+            ("--script", _) if !value.is_empty() => script = Some(value.to_string()),
+            _ => {
+                print_usage();
+                return;
             }
         }
     }
+
+    if let Some(path) = script {
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                session.colored = false;
+                print!("{}", session.run_script(&text));
+            }
+            Err(error) => println!("Could not read {}: {}", path, error),
+        }
+        return;
+    }
+
+    // The computer starts if it plays white
+    if session.computer == Some(ChessPieceColor::White) {
+        let chess_move = session.game.best_move(chess_engine::chess_game::DEFAULT_SEARCH_DEPTH).unwrap();
+        println!("Computer plays {}", session.game.move_to_san(&chess_move));
+        session.game.move_piece(chess_move.board_move, true, chess_move.promote_piece).unwrap();
+    }
+    print!("{}", session.get_game_info());
+    println!("Type a move in algebraic notation, ex Nf3, or help for a list of commands");
+    let stdin = io::stdin();
+    for line in stdin.lock().lines().map(|l| l.unwrap()) {
+        match session.execute_line(&line) {
+            Ok(text) => print!("{}", text),
+            Err(error) => println!("{}", error),
+        }
+    }
 }
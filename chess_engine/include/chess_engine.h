@@ -0,0 +1,84 @@
+/*
+ * C interface of chess_engine, built as libchess_engine with the "ffi" feature:
+ *
+ *     cargo build --release --features ffi
+ *
+ * Games are opaque handles made by chess_game_new and released with chess_game_free.
+ * Every pointer passed in must be null or valid, strings are NUL terminated UTF-8.
+ * Internal errors are returned as CHESS_ERROR_INTERNAL, they never unwind into the caller.
+ * A handle must not be used from several threads at the same time.
+ */
+#ifndef CHESS_ENGINE_H
+#define CHESS_ENGINE_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Size of a move in UCI notation with its terminating NUL, ex "e7e8q" */
+#define CHESS_UCI_MOVE_SIZE 6
+/* No position has more legal moves than this */
+#define CHESS_MAX_LEGAL_MOVES 218
+/* Large enough for any FEN */
+#define CHESS_FEN_BUFFER_SIZE 128
+
+typedef struct FfiGame ChessGame;
+
+typedef enum ChessError {
+    CHESS_OK = 0,
+    CHESS_ERROR_NULL_POINTER = 1,
+    CHESS_ERROR_INVALID_UTF8 = 2,
+    CHESS_ERROR_INVALID_FEN = 3,
+    CHESS_ERROR_INVALID_MOVE_TEXT = 4,    /* Not a move in UCI notation */
+    CHESS_ERROR_NO_PIECE = 5,             /* No piece on the from square */
+    CHESS_ERROR_NOT_YOUR_PIECE = 6,       /* The piece belongs to the player not to move */
+    CHESS_ERROR_OWN_PIECE_ON_TARGET = 7,
+    CHESS_ERROR_LEAVES_KING_IN_CHECK = 8,
+    CHESS_ERROR_PROMOTION = 9,            /* Missing or invalid promotion piece */
+    CHESS_ERROR_GAME_OVER = 10,
+    CHESS_ERROR_ILLEGAL_MOVE = 11,        /* The piece can not move like that */
+    CHESS_ERROR_BUFFER_TOO_SMALL = 12,
+    CHESS_ERROR_INTERNAL = 13
+} ChessError;
+
+typedef enum ChessOutcome {
+    CHESS_OUTCOME_ONGOING = 0,
+    CHESS_OUTCOME_WHITE_WINS = 1,
+    CHESS_OUTCOME_BLACK_WINS = 2,
+    CHESS_OUTCOME_DRAW = 3
+} ChessOutcome;
+
+/* A game in the standard start position */
+ChessGame *chess_game_new(void);
+void chess_game_free(ChessGame *game);
+
+/* Replace the position, the game is unchanged on errors */
+int chess_game_load_fen(ChessGame *game, const char *fen);
+/* Write the FEN of the position into buffer, which holds size bytes */
+int chess_game_fen(const ChessGame *game, char *buffer, size_t size);
+
+/* Play a move in UCI notation, ex "e2e4" or "e7e8q". Returns a ChessError */
+int chess_game_make_move(ChessGame *game, const char *uci);
+
+/*
+ * Write the legal moves into buffer as NUL terminated strings of CHESS_UCI_MOVE_SIZE bytes,
+ * move i starts at buffer + i * CHESS_UCI_MOVE_SIZE. The buffer holds capacity moves.
+ * count is set to the number of legal moves, also when CHESS_ERROR_BUFFER_TOO_SMALL is returned.
+ */
+int chess_game_legal_moves(const ChessGame *game, char *buffer, size_t capacity, size_t *count);
+
+/* Returns a ChessOutcome, CHESS_OUTCOME_ONGOING if game is null or the outcome can not be found */
+int chess_game_outcome(const ChessGame *game);
+/* Returns 1 if the player to move is in check, else 0, also if game is null or it can not be found */
+int chess_game_is_check(const ChessGame *game);
+
+/* Message of the last error, empty after a call that succeeded. Valid until the next call with the game */
+const char *chess_game_last_error(const ChessGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,36 @@
+use chess_engine::chess_game::ApiServer;
+
+const DEFAULT_ADDRESS: &str = "0.0.0.0:8080";
+
+fn print_usage() {
+    println!("Usage: chess_http [--address ADDRESS]");
+    println!("  --address  Address to listen on, default {}", DEFAULT_ADDRESS);
+    println!();
+    println!("Every endpoint takes a POST with a JSON body such as {{\"fen\": \"...\", \"moves\": [\"e2e4\", \"e5\"]}}:");
+    println!("  /legal-moves  The legal moves in UCI and algebraic notation");
+    println!("  /apply-move   Play {{\"move\": \"Nf3\"}} and get the new status");
+    println!("  /status       Check, checkmate, stalemate and the result");
+    println!("  /best-move    The move the built-in search picks, {{\"depth\": 3}} to search deeper");
+    println!("  /render.svg   A board diagram, with optional orientation, size and coordinates");
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let address = match args.as_slice() {
+        [] => DEFAULT_ADDRESS,
+        [option, address] if option == "--address" => address.as_str(),
+        _ => {
+            print_usage();
+            return;
+        }
+    };
+    let server = match ApiServer::bind(address) {
+        Ok(server) => server,
+        Err(error) => {
+            println!("Could not listen on {}: {}", address, error);
+            return;
+        }
+    };
+    println!("Listening on http://{}", server.local_addr().unwrap());
+    server.run();
+}
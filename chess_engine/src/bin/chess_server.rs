@@ -0,0 +1,40 @@
+use chess_engine::chess_game::PlayServer;
+
+const DEFAULT_ADDRESS: &str = "0.0.0.0:7878";
+
+fn print_usage() {
+    println!("Usage: chess_server [--address ADDRESS]");
+    println!("  --address  Address to listen on, default {}", DEFAULT_ADDRESS);
+    println!();
+    println!("Clients send one message on each line, or JSON messages over a WebSocket on the same port:");
+    println!("  create [white|black|random] [TIME CONTROL]   Start a game and get a code for the opponent");
+    println!("  join CODE                                    Play in a game");
+    println!("  watch CODE                                   Watch a game");
+    println!("  resume CODE TOKEN                            Take a seat back after losing the connection");
+    println!("  move MOVE                                    Play a move, ex e2e4 or Nf3");
+    println!("  resign");
+    println!("  state");
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let address = match args.as_slice() {
+        [] => DEFAULT_ADDRESS,
+        [option, address] if option == "--address" => address.as_str(),
+        _ => {
+            print_usage();
+            return;
+        }
+    };
+    let server = match PlayServer::bind(address) {
+        Ok(server) => server,
+        Err(error) => {
+            println!("Could not listen on {}: {}", address, error);
+            return;
+        }
+    };
+    println!("Listening on {}", server.local_addr().unwrap());
+    if let Err(error) = server.run() {
+        println!("{}", error);
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    fs,
+    io::{self, Write},
+    time::Duration,
+};
+
+use chess_engine::chess_game::*;
+use crossterm::{
+    cursor,
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEventKind},
+    queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+
+// Where the board is drawn in the terminal, each square is three columns wide
+const BOARD_LEFT: u16 = 2;
+const BOARD_TOP: u16 = 1;
+const SQUARE_WIDTH: u16 = 3;
+const PANEL_LEFT: u16 = 32;
+const MOVE_LIST_LINES: usize = 12;
+
+const HELP: &str = "Arrows/click: select  Enter: move  u: undo  f: flip  c: computer  :  command  q: quit";
+const COMMAND_HELP: &str = "Commands: fen [FEN], load FILE, save FILE, time CONTROL, new, quit";
+
+// Restores the terminal when the program ends, even if it panics
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(io::stdout(), terminal::EnterAlternateScreen, EnableMouseCapture, cursor::Hide)?;
+        return Ok(Self);
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(io::stdout(), cursor::Show, DisableMouseCapture, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+struct App {
+    game: Game,
+    cursor: BoardPosition,
+    selected: Option<BoardPosition>,
+    orientation: ChessPieceColor,
+    computer: Option<ChessPieceColor>,
+    time_control: Option<TimeControl>,
+    clock: Option<Clock>,
+    pending_promotion: Option<BoardMove>,
+    prompt: Option<String>, // Command being typed after ':'
+    message: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(game: Game) -> Self {
+        return Self {
+            game,
+            cursor: BoardPosition::new(4, 6),
+            selected: None,
+            orientation: ChessPieceColor::White,
+            computer: None,
+            time_control: None,
+            clock: None,
+            pending_promotion: None,
+            prompt: None,
+            message: HELP.to_string(),
+            quit: false,
+        };
+    }
+
+    // Start a new game from a position, with fresh clocks
+    fn set_game(&mut self, game: Game) {
+        self.game = game;
+        self.selected = None;
+        self.pending_promotion = None;
+        self.reset_clock();
+    }
+
+    fn reset_clock(&mut self) {
+        self.clock = self.time_control.clone().map(Clock::with_system_time);
+        if self.clock.is_some() && !self.game.game_is_over() {
+            self.clock.as_mut().unwrap().start(self.game.turn);
+        }
+    }
+
+    // Get the square drawn at a column and row of the board, counted from the top left
+    fn get_board_pos(&self, column: i32, row: i32) -> Option<BoardPosition> {
+        if !(0..8).contains(&column) || !(0..8).contains(&row) {
+            return None;
+        }
+        return Some(self.get_render_options().get_rows()[row as usize][column as usize]);
+    }
+
+    // Get the column and row a square is drawn in
+    fn get_screen_pos(&self, pos: BoardPosition) -> (i32, i32) {
+        for (row, positions) in self.get_render_options().get_rows().iter().enumerate() {
+            if let Some(column) = positions.iter().position(|&board_pos| board_pos == pos) {
+                return (column as i32, row as i32);
+            }
+        }
+        return (0, 0);
+    }
+
+    fn get_render_options(&self) -> RenderOptions {
+        return RenderOptions { orientation: self.orientation, ..RenderOptions::default() };
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.prompt.is_some() {
+            self.handle_prompt_key(key);
+            return;
+        }
+        if self.pending_promotion.is_some() {
+            let id = match key.code {
+                KeyCode::Char(letter) => ChessPieceId::from_uci_letter(letter.to_ascii_lowercase()).ok(),
+                _ => None,
+            };
+            let board_move = self.pending_promotion.take().unwrap();
+            match id {
+                Some(id) => self.play_move(Move::new(board_move, Some(id))),
+                None => self.message = "Promotion cancelled".to_string(),
+            }
+            return;
+        }
+        let (column, row) = self.get_screen_pos(self.cursor);
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') => self.move_cursor(column - 1, row),
+            KeyCode::Right | KeyCode::Char('l') => self.move_cursor(column + 1, row),
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(column, row - 1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(column, row + 1),
+            KeyCode::Enter | KeyCode::Char(' ') => self.select(self.cursor),
+            KeyCode::Esc => self.selected = None,
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('f') => self.flip(),
+            KeyCode::Char('c') => self.toggle_computer(),
+            KeyCode::Char('n') => self.run_command("new"),
+            KeyCode::Char(':') => {
+                self.prompt = Some(String::new());
+                self.message = COMMAND_HELP.to_string();
+            }
+            KeyCode::Char('q') => self.quit = true,
+            _ => {}
+        }
+    }
+
+    fn handle_prompt_key(&mut self, key: KeyEvent) {
+        let prompt = self.prompt.as_mut().unwrap();
+        match key.code {
+            KeyCode::Char(letter) => prompt.push(letter),
+            KeyCode::Backspace => {
+                prompt.pop();
+            }
+            KeyCode::Esc => {
+                self.prompt = None;
+                self.message = HELP.to_string();
+            }
+            KeyCode::Enter => {
+                let command = self.prompt.take().unwrap();
+                self.run_command(command.trim());
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_click(&mut self, column: u16, row: u16) {
+        if column < BOARD_LEFT || row < BOARD_TOP || self.prompt.is_some() {
+            return;
+        }
+        let pos = self.get_board_pos(((column - BOARD_LEFT) / SQUARE_WIDTH) as i32, (row - BOARD_TOP) as i32);
+        if pos.is_some() {
+            self.cursor = pos.unwrap();
+            self.select(pos.unwrap());
+        }
+    }
+
+    fn move_cursor(&mut self, column: i32, row: i32) {
+        if let Some(pos) = self.get_board_pos(column, row) {
+            self.cursor = pos;
+        }
+    }
+
+    // Select a piece to move, or move the selected piece to the square
+    fn select(&mut self, pos: BoardPosition) {
+        if self.game.game_is_over() || self.computer == Some(self.game.turn) {
+            return;
+        }
+        let piece = self.game.piece_at(pos);
+        if piece.is_some() && piece.unwrap().color == self.game.turn {
+            self.selected = Some(pos);
+            return;
+        }
+        if self.selected.is_none() {
+            return;
+        }
+        let from_pos = self.selected.take().unwrap();
+        let board_move = BoardMove { from_pos, to_pos: pos };
+        let is_promotion = self.game.legal_moves().iter()
+            .any(|legal_move| legal_move.board_move.to_uci() == board_move.to_uci() && legal_move.promote_piece.is_some());
+        if is_promotion {
+            self.pending_promotion = Some(board_move);
+            self.message = "Promote to (q)ueen, (r)ook, (b)ishop or k(n)ight".to_string();
+            return;
+        }
+        self.play_move(Move::new(board_move, None));
+    }
+
+    fn play_move(&mut self, chess_move: Move) {
+        let san = self.game.move_to_san(&chess_move);
+        match self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece) {
+            Ok(()) => {
+                self.message = format!("Played {}", san);
+                self.press_clock();
+            }
+            Err(error) => self.message = error,
+        }
+    }
+
+    fn press_clock(&mut self) {
+        if self.clock.is_none() {
+            return;
+        }
+        let clock = self.clock.as_mut().unwrap();
+        let _ = clock.press();
+        if self.game.game_is_over() {
+            clock.stop();
+        }
+    }
+
+    // Let the computer move if it is its turn
+    fn play_computer_move(&mut self) {
+        if self.computer != Some(self.game.turn) || self.game.game_is_over() {
+            return;
+        }
+        if let Some(chess_move) = self.game.best_move(DEFAULT_SEARCH_DEPTH) {
+            self.play_move(chess_move);
+        }
+    }
+
+    // Take back a move, or two when playing the computer so it is the human's turn again
+    fn undo(&mut self) {
+        let mut undone = Vec::new();
+        while let Ok(entry) = self.game.undo_move() {
+            undone.push(entry.san);
+            if self.computer != Some(self.game.turn) {
+                break;
+            }
+        }
+        if undone.is_empty() {
+            self.message = "No move to undo".to_string();
+            return;
+        }
+        self.selected = None;
+        self.message = format!("Took back {}", undone.join(", "));
+        if let Some(clock) = self.clock.as_mut() {
+            clock.start(self.game.turn);
+        }
+    }
+
+    fn flip(&mut self) {
+        self.orientation = match self.orientation {
+            ChessPieceColor::White => ChessPieceColor::Black,
+            ChessPieceColor::Black => ChessPieceColor::White,
+        };
+    }
+
+    // Let the computer play black, then white, then no side
+    fn toggle_computer(&mut self) {
+        self.computer = match self.computer {
+            None => Some(ChessPieceColor::Black),
+            Some(ChessPieceColor::Black) => Some(ChessPieceColor::White),
+            Some(ChessPieceColor::White) => None,
+        };
+        self.message = match self.computer {
+            Some(color) => format!("The computer plays {:?}", color),
+            None => "Two human players".to_string(),
+        };
+    }
+
+    fn run_command(&mut self, command: &str) {
+        let (name, argument) = match command.split_once(' ') {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
+        let result: Result<String, String> = match name {
+            "fen" if argument.is_empty() => Ok(self.game.to_fen()),
+            "fen" => Game::from_fen(argument).map(|game| {
+                self.set_game(game);
+                return "Position loaded".to_string();
+            }),
+            "load" => self.load(argument),
+            "save" => fs::write(argument, self.game.to_pgn())
+                .map(|_| format!("Saved {}", argument))
+                .map_err(|error| error.to_string()),
+            "time" => argument.parse::<TimeControl>().map(|time_control| {
+                self.time_control = Some(time_control);
+                self.reset_clock();
+                return format!("Time control {}", argument);
+            }),
+            "new" => {
+                let mut game = Game::new();
+                game.set_up_board();
+                self.set_game(game);
+                Ok("New game".to_string())
+            }
+            "quit" => {
+                self.quit = true;
+                Ok(String::new())
+            }
+            _ => Err(format!("Unknown command '{}'. {}", name, COMMAND_HELP)),
+        };
+        match result {
+            Ok(message) => self.message = message,
+            Err(error) => self.message = error,
+        }
+    }
+
+    // Load a game from a file with a FEN position or a PGN game
+    fn load(&mut self, path: &str) -> Result<String, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let game = Game::from_fen(text.trim()).or_else(|_| Game::from_pgn(&text))?;
+        self.set_game(game);
+        return Ok(format!("Loaded {}", path));
+    }
+
+    fn update_clock(&mut self) {
+        if let Some(clock) = self.clock.as_mut() {
+            if self.game.update_from_clock(clock) {
+                clock.stop();
+            }
+        }
+    }
+
+    fn get_status(&self) -> String {
+        if !self.game.game_is_over() {
+            let check = if self.game.is_check().is_some() { ", check!" } else { "" };
+            return format!("{:?} to move{}", self.game.turn, check);
+        }
+        if let Some(color) = self.game.flag_fall() {
+            return match self.game.get_winner() {
+                Some(winner) => format!("{:?} ran out of time, {:?} wins", color, winner),
+                None => format!("{:?} ran out of time, draw", color),
+            };
+        }
+        return match self.game.get_winner() {
+            Some(winner) => format!("Checkmate, {:?} wins", winner),
+            None => "Draw".to_string(),
+        };
+    }
+
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        queue!(out, terminal::Clear(ClearType::All))?;
+        self.draw_board(out)?;
+
+        // Status, clocks and moves next to the board
+        let mut line = BOARD_TOP;
+        queue!(out, cursor::MoveTo(PANEL_LEFT, line), Print(self.get_status()))?;
+        line += 1;
+        if let Some(clock) = self.clock.as_ref() {
+            for color in [ChessPieceColor::White, ChessPieceColor::Black] {
+                let remaining = clock.remaining(color).as_secs();
+                let running = if clock.running() == Some(color) { " <" } else { "" };
+                queue!(out, cursor::MoveTo(PANEL_LEFT, line),
+                    Print(format!("{:?}: {}:{:02}{}", color, remaining / 60, remaining % 60, running)))?;
+                line += 1;
+            }
+        }
+        line += 1;
+        let history = self.game.history();
+        let first_white_move = if self.game.start_fen().contains(" b ") { 1 } else { 0 };
+        let mut move_lines: Vec<String> = Vec::new();
+        for (index, entry) in history.iter().enumerate() {
+            let ply = index + first_white_move;
+            if ply % 2 == 0 || index == 0 {
+                let number = ply / 2 + 1;
+                let dots = if ply % 2 == 0 { "." } else { "..." };
+                move_lines.push(format!("{:>3}{} {}", number, dots, entry.san));
+            }
+            else {
+                let line = move_lines.last_mut().unwrap();
+                *line = format!("{:<14} {}", line, entry.san);
+            }
+        }
+        for text in move_lines.iter().skip(move_lines.len().saturating_sub(MOVE_LIST_LINES)) {
+            queue!(out, cursor::MoveTo(PANEL_LEFT, line), Print(text))?;
+            line += 1;
+        }
+
+        let bottom = BOARD_TOP + 10;
+        match self.prompt.as_ref() {
+            Some(prompt) => queue!(out, cursor::MoveTo(0, bottom), Print(format!(":{}", prompt)))?,
+            None => queue!(out, cursor::MoveTo(0, bottom), Print(&self.message))?,
+        }
+        if self.prompt.is_some() {
+            queue!(out, cursor::MoveTo(0, bottom + 1), Print(&self.message))?;
+        }
+        return out.flush();
+    }
+
+    fn draw_board(&self, out: &mut impl Write) -> io::Result<()> {
+        let targets: Vec<BoardPosition> = match self.selected {
+            Some(from_pos) => self.game.legal_moves().iter()
+                .filter(|legal_move| legal_move.board_move.from_pos == from_pos)
+                .map(|legal_move| legal_move.board_move.to_pos)
+                .collect(),
+            None => Vec::new(),
+        };
+        let last_move = self.game.last_move();
+        for row in 0..8 {
+            let rank = self.get_board_pos(0, row).unwrap().to_algebraic_notation().chars().nth(1).unwrap();
+            queue!(out, cursor::MoveTo(0, BOARD_TOP + row as u16), Print(rank))?;
+            for column in 0..8 {
+                let pos = self.get_board_pos(column, row).unwrap();
+                // Flipping the board keeps the colors of the squares where they are drawn
+                let light = (row + column) % 2 == 0;
+                let mut background = if light { Color::Rgb { r: 240, g: 217, b: 181 } } else { Color::Rgb { r: 181, g: 136, b: 99 } };
+                if last_move.is_some() && (last_move.unwrap().from_pos == pos || last_move.unwrap().to_pos == pos) {
+                    background = Color::Rgb { r: 205, g: 210, b: 106 };
+                }
+                if self.selected == Some(pos) {
+                    background = Color::Rgb { r: 100, g: 170, b: 90 };
+                }
+                let piece = self.game.piece_at(pos);
+                let glyph = match piece {
+                    // The filled glyphs are easier to see, the color shows whose piece it is
+                    Some(piece) => ChessPiece::get_unicode_char(piece.id, ChessPieceColor::Black),
+                    None if targets.contains(&pos) => '•',
+                    None => ' ',
+                };
+                let foreground = match piece {
+                    Some(piece) if piece.color == ChessPieceColor::White => Color::White,
+                    Some(_) => Color::Black,
+                    None => Color::DarkGreen,
+                };
+                let (left, right) = if self.cursor == pos { ('[', ']') } else if targets.contains(&pos) && piece.is_some() { ('(', ')') } else { (' ', ' ') };
+                queue!(out,
+                    cursor::MoveTo(BOARD_LEFT + column as u16 * SQUARE_WIDTH, BOARD_TOP + row as u16),
+                    SetBackgroundColor(background),
+                    SetForegroundColor(Color::Black),
+                    Print(left),
+                    SetForegroundColor(foreground),
+                    Print(glyph),
+                    SetForegroundColor(Color::Black),
+                    Print(right),
+                    ResetColor,
+                )?;
+            }
+        }
+        for column in 0..8 {
+            let file = self.get_board_pos(column, 0).unwrap().to_algebraic_notation().chars().next().unwrap();
+            queue!(out, cursor::MoveTo(BOARD_LEFT + column as u16 * SQUARE_WIDTH + 1, BOARD_TOP + 8), Print(file))?;
+        }
+        return Ok(());
+    }
+}
+
+fn print_usage() {
+    println!("Usage: chess_tui [--computer white|black] [--time CONTROL] [--fen FEN] [--pgn FILE]");
+    println!("  --computer  Let the built-in move picker play a side");
+    println!("  --time      Time control as in PGN, ex 300+2 or 40/5400:1800+30");
+    println!("  --fen       Start from a position");
+    println!("  --pgn       Continue a game saved in PGN");
+}
+
+fn main() -> io::Result<()> {
+    let mut game = Game::new();
+    game.set_up_board();
+    let mut app = App::new(game);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    for option in args.chunks(2) {
+        let value = option.get(1).map(|value| value.as_str()).unwrap_or("");
+        let result: Result<(), String> = match option[0].as_str() {
+            "--computer" => match value {
+                "white" => Ok(app.computer = Some(ChessPieceColor::White)),
+                "black" => Ok(app.computer = Some(ChessPieceColor::Black)),
+                _ => Err("--computer must be white or black".to_string()),
+            },
+            "--time" => value.parse::<TimeControl>().map(|time_control| app.time_control = Some(time_control)),
+            "--fen" => Game::from_fen(value).map(|game| app.game = game),
+            "--pgn" => fs::read_to_string(value)
+                .map_err(|error| error.to_string())
+                .and_then(|text| Game::from_pgn(&text))
+                .map(|game| app.game = game),
+            _ => Err(format!("Unknown option {}", option[0])),
+        };
+        if let Err(error) = result {
+            println!("{}", error);
+            print_usage();
+            return Ok(());
+        }
+    }
+    // Show the board from the human's side
+    if app.computer == Some(ChessPieceColor::White) {
+        app.orientation = ChessPieceColor::Black;
+    }
+    app.reset_clock();
+
+    let _guard = TerminalGuard::new()?;
+    let mut stdout = io::stdout();
+    while !app.quit {
+        app.update_clock();
+        app.draw(&mut stdout)?;
+        if app.computer == Some(app.game.turn) && !app.game.game_is_over() {
+            app.play_computer_move();
+            continue;
+        }
+        // Wake up now and then to update the clocks
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+        match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => app.handle_key(key),
+            Event::Mouse(mouse) if mouse.kind == MouseEventKind::Down(MouseButton::Left) => app.handle_click(mouse.column, mouse.row),
+            _ => {}
+        }
+    }
+    return Ok(());
+}
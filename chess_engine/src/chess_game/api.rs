@@ -0,0 +1,237 @@
+use std::{net::SocketAddr, thread};
+
+use serde::{Deserialize, Serialize};
+
+use super::{ChessPieceColor, Game, Move, SvgOptions, DEFAULT_SEARCH_DEPTH};
+
+// Deepest search a client can ask for, deeper searches take too long for a request
+pub const MAX_API_SEARCH_DEPTH: u32 = 4;
+
+// Body of a request to the HTTP API. The position is the FEN, or the start position,
+// followed by the moves in UCI or algebraic notation
+#[derive(Clone, PartialEq, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ApiRequest {
+    pub fen: Option<String>,
+    pub moves: Vec<String>,
+    #[serde(rename = "move")]
+    pub chess_move: Option<String>, // The move to apply for /apply-move
+    pub depth: Option<u32>, // Search depth for /best-move
+    pub orientation: Option<ChessPieceColor>, // For /render.svg
+    pub size: Option<u32>,
+    pub coordinates: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiMove {
+    pub uci: String,
+    pub san: String,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiStatus {
+    pub fen: String,
+    pub turn: ChessPieceColor,
+    pub check: bool,
+    pub checkmate: bool,
+    pub stalemate: bool,
+    pub game_over: bool,
+    pub result: String, // As in PGN, "*" while the game goes on
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiLegalMoves {
+    pub fen: String,
+    pub moves: Vec<ApiMove>,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiAppliedMove {
+    #[serde(rename = "move")]
+    pub chess_move: ApiMove,
+    pub status: ApiStatus,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiBestMove {
+    #[serde(rename = "move")]
+    pub chess_move: Option<ApiMove>, // None when there are no legal moves
+    pub score: Option<i32>, // Centipawns for the player to move
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct ApiError {
+    pub error: String,
+}
+
+// What to send back for a request
+#[derive(Clone, PartialEq, Debug)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl ApiResponse {
+    fn json<T: Serialize>(value: &T) -> ApiResponse {
+        return ApiResponse { status: 200, content_type: "application/json", body: serde_json::to_string(value).unwrap() };
+    }
+
+    fn error(status: u16, error: &str) -> ApiResponse {
+        return ApiResponse {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_string(&ApiError { error: error.to_string() }).unwrap(),
+        };
+    }
+}
+
+fn get_api_move(game: &Game, chess_move: &Move) -> ApiMove {
+    return ApiMove { uci: chess_move.to_uci(), san: game.move_to_san(chess_move) };
+}
+
+fn get_status(game: &Game) -> ApiStatus {
+    let game_over = game.game_is_over();
+    let result = match game.get_winner() {
+        Some(ChessPieceColor::White) => "1-0",
+        Some(ChessPieceColor::Black) => "0-1",
+        None if game_over => "1/2-1/2",
+        None => "*",
+    };
+    return ApiStatus {
+        fen: game.to_fen(),
+        turn: game.turn,
+        check: game.is_check().is_some(),
+        checkmate: game.is_check_mate(),
+        stalemate: game.is_stale_mate(),
+        game_over,
+        result: result.to_string(),
+    };
+}
+
+impl ApiRequest {
+    // Set up the position of the request
+    pub fn get_game(&self) -> Result<Game, String> {
+        let mut game = match self.fen.as_ref() {
+            Some(fen) => Game::from_fen(fen)?,
+            None => {
+                let mut game = Game::new();
+                game.set_up_board();
+                game
+            }
+        };
+        for text in &self.moves {
+            let chess_move = game.parse_move(text).map_err(|error| format!("Move {}: {}", text, error))?;
+            game.move_piece(chess_move.board_move, true, chess_move.promote_piece)
+                .map_err(|error| format!("Move {}: {}", text, error))?;
+        }
+        return Ok(game);
+    }
+}
+
+// Answer a request to the HTTP API, without any networking
+pub fn handle_api_request(method: &str, path: &str, body: &str) -> ApiResponse {
+    let path = path.split('?').next().unwrap_or("");
+    if !["/legal-moves", "/apply-move", "/status", "/best-move", "/render.svg"].contains(&path) {
+        return ApiResponse::error(404, &format!("Unknown path {}", path));
+    }
+    if method != "POST" {
+        return ApiResponse::error(405, "Use POST with a JSON body");
+    }
+    let request: ApiRequest = match serde_json::from_str(if body.trim().is_empty() { "{}" } else { body }) {
+        Ok(request) => request,
+        Err(error) => return ApiResponse::error(400, &format!("Invalid JSON: {}", error)),
+    };
+    let game = match request.get_game() {
+        Ok(game) => game,
+        Err(error) => return ApiResponse::error(400, &error),
+    };
+    match path {
+        "/legal-moves" => {
+            let moves = game.legal_moves().iter().map(|chess_move| get_api_move(&game, chess_move)).collect();
+            return ApiResponse::json(&ApiLegalMoves { fen: game.to_fen(), moves });
+        }
+        "/apply-move" => {
+            if request.chess_move.is_none() {
+                return ApiResponse::error(400, "Missing move");
+            }
+            let mut game = game;
+            let text = request.chess_move.unwrap();
+            let chess_move = match game.parse_move(&text) {
+                Ok(chess_move) => chess_move,
+                Err(error) => return ApiResponse::error(400, &error),
+            };
+            let api_move = get_api_move(&game, &chess_move);
+            if let Err(error) = game.move_piece(chess_move.board_move, true, chess_move.promote_piece) {
+                return ApiResponse::error(400, &error);
+            }
+            return ApiResponse::json(&ApiAppliedMove { chess_move: api_move, status: get_status(&game) });
+        }
+        "/status" => return ApiResponse::json(&get_status(&game)),
+        "/best-move" => {
+            let depth = request.depth.unwrap_or(DEFAULT_SEARCH_DEPTH);
+            if depth == 0 || depth > MAX_API_SEARCH_DEPTH {
+                return ApiResponse::error(400, &format!("The depth must be from 1 to {}", MAX_API_SEARCH_DEPTH));
+            }
+            let best = game.search(depth);
+            return ApiResponse::json(&ApiBestMove {
+                chess_move: best.map(|(chess_move, _)| get_api_move(&game, &chess_move)),
+                score: best.map(|(_, score)| score),
+            });
+        }
+        _ => {
+            let default_options = SvgOptions::default();
+            let options = SvgOptions {
+                size: request.size.unwrap_or(default_options.size),
+                orientation: request.orientation.unwrap_or(default_options.orientation),
+                coordinates: request.coordinates.unwrap_or(default_options.coordinates),
+                ..default_options
+            };
+            return ApiResponse { status: 200, content_type: "image/svg+xml", body: game.to_svg(&options) };
+        }
+    }
+}
+
+// Serves the HTTP API, answering each request on its own thread
+pub struct ApiServer {
+    server: tiny_http::Server,
+}
+
+impl ApiServer {
+    // Listen on an address, ex "0.0.0.0:8080", or "127.0.0.1:0" for any free port
+    pub fn bind(address: &str) -> Result<ApiServer, String> {
+        let server = tiny_http::Server::http(address).map_err(|error| error.to_string())?;
+        return Ok(ApiServer { server });
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        return self.server.server_addr().to_ip().ok_or_else(|| "Not listening on an IP address".to_string());
+    }
+
+    pub fn run(self) {
+        for mut request in self.server.incoming_requests() {
+            thread::spawn(move || {
+                // Let web pages on other origins call the API
+                let allow_origin = tiny_http::Header::from_bytes("Access-Control-Allow-Origin", "*").unwrap();
+                if request.method() == &tiny_http::Method::Options {
+                    let response = tiny_http::Response::empty(204)
+                        .with_header(allow_origin)
+                        .with_header(tiny_http::Header::from_bytes("Access-Control-Allow-Methods", "POST").unwrap())
+                        .with_header(tiny_http::Header::from_bytes("Access-Control-Allow-Headers", "Content-Type").unwrap());
+                    let _ = request.respond(response);
+                    return;
+                }
+                let mut body = String::new();
+                let response = match request.as_reader().read_to_string(&mut body) {
+                    Ok(_) => handle_api_request(request.method().as_str(), request.url(), &body),
+                    Err(_) => ApiResponse::error(400, "The body must be UTF-8"),
+                };
+                let response = tiny_http::Response::from_string(response.body)
+                    .with_status_code(response.status)
+                    .with_header(tiny_http::Header::from_bytes("Content-Type", response.content_type).unwrap())
+                    .with_header(allow_origin);
+                let _ = request.respond(response);
+            });
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use super::ChessPieceColor;
+
+// The side of the board the king castles towards, king side is towards the h file
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CastleSide {
+    KingSide,
+    QueenSide,
+}
+
+// Which castling moves are still allowed, stored as one bit per color and side
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    bits: u8,
+}
+
+impl CastlingRights {
+    pub const WHITE_KING_SIDE: u8 = 1;
+    pub const WHITE_QUEEN_SIDE: u8 = 2;
+    pub const BLACK_KING_SIDE: u8 = 4;
+    pub const BLACK_QUEEN_SIDE: u8 = 8;
+
+    pub fn none() -> Self {
+        return Self { bits: 0 };
+    }
+
+    pub fn all() -> Self {
+        return Self::from_bits(15);
+    }
+
+    // Create rights from bits, bits other than the four castling bits are ignored
+    pub fn from_bits(bits: u8) -> Self {
+        return Self { bits: bits & 15 };
+    }
+
+    pub fn bits(&self) -> u8 {
+        return self.bits;
+    }
+
+    fn get_bit(color: ChessPieceColor, side: CastleSide) -> u8 {
+        match (color, side) {
+            (ChessPieceColor::White, CastleSide::KingSide) => return Self::WHITE_KING_SIDE,
+            (ChessPieceColor::White, CastleSide::QueenSide) => return Self::WHITE_QUEEN_SIDE,
+            (ChessPieceColor::Black, CastleSide::KingSide) => return Self::BLACK_KING_SIDE,
+            (ChessPieceColor::Black, CastleSide::QueenSide) => return Self::BLACK_QUEEN_SIDE,
+        }
+    }
+
+    pub fn has(&self, color: ChessPieceColor, side: CastleSide) -> bool {
+        return self.bits & Self::get_bit(color, side) != 0;
+    }
+
+    pub fn set(&mut self, color: ChessPieceColor, side: CastleSide, allowed: bool) {
+        if allowed {
+            self.bits |= Self::get_bit(color, side);
+        }
+        else {
+            self.bits &= !Self::get_bit(color, side);
+        }
+    }
+
+    // Remove both rights of a color, ex when the king has moved
+    pub fn remove_color(&mut self, color: ChessPieceColor) {
+        self.set(color, CastleSide::KingSide, false);
+        self.set(color, CastleSide::QueenSide, false);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.bits == 0;
+    }
+}
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        return Self::all();
+    }
+}
@@ -0,0 +1,384 @@
+use std::{
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+use super::{ChessPieceColor, ChessPieceId, Game};
+
+// Gives the time that has passed since some fixed point, used so the clock can be tested without waiting
+pub trait TimeSource {
+    fn now(&self) -> Duration;
+}
+
+// Get the wall clock time
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn get_current_time() -> SystemTime {
+    return SystemTime::now();
+}
+
+// Rust has no clock in browsers, so the time comes from JavaScript
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub(crate) fn get_current_time() -> SystemTime {
+    return SystemTime::UNIX_EPOCH + Duration::from_millis(js_sys::Date::now() as u64);
+}
+
+// Without JavaScript there is no clock, so the time stays at the epoch
+#[cfg(all(target_arch = "wasm32", not(feature = "wasm")))]
+pub(crate) fn get_current_time() -> SystemTime {
+    return SystemTime::UNIX_EPOCH;
+}
+
+// Time source using the system's monotonic clock, or the wall clock on wasm32 where there is no monotonic clock
+#[derive(Clone, Copy, Debug)]
+pub struct SystemTimeSource {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: Instant,
+    #[cfg(target_arch = "wasm32")]
+    start: SystemTime,
+}
+
+impl SystemTimeSource {
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new() -> Self {
+        return Self { start: Instant::now() };
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new() -> Self {
+        return Self { start: get_current_time() };
+    }
+}
+
+impl Default for SystemTimeSource {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl TimeSource for SystemTimeSource {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn now(&self) -> Duration {
+        return self.start.elapsed();
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn now(&self) -> Duration {
+        return get_current_time().duration_since(self.start).unwrap_or_default();
+    }
+}
+
+// Time source that only moves when told to, copies share the same time
+#[derive(Clone, Debug, Default)]
+pub struct ManualTimeSource {
+    time: Arc<Mutex<Duration>>,
+}
+
+impl ManualTimeSource {
+    pub fn new() -> Self {
+        return Self::default();
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.time.lock().unwrap() += duration;
+    }
+}
+
+impl TimeSource for ManualTimeSource {
+    fn now(&self) -> Duration {
+        return *self.time.lock().unwrap();
+    }
+}
+
+// How a player gets time back for each move
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Increment {
+    None,
+    Fischer(Duration), // Added after every move
+    Bronstein(Duration), // The time used is given back after the move, up to the delay
+    SimpleDelay(Duration), // The clock does not start counting until the delay has passed
+}
+
+// A part of a time control, ex 40 moves in 90 minutes
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeStage {
+    pub moves: Option<u32>, // None if the stage lasts for the rest of the game
+    pub time: Duration,
+    pub increment: Increment,
+}
+
+// The stages of a time control, ex 40/90 followed by 30 minutes with 30 seconds increment.
+// When the moves of the last stage are done the last stage is played again
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeControl {
+    pub stages: Vec<TimeStage>,
+}
+
+impl TimeControl {
+    pub fn new(stages: Vec<TimeStage>) -> Result<Self, String> {
+        if stages.is_empty() {
+            return Err("A time control needs at least one stage".to_string());
+        }
+        return Ok(Self { stages });
+    }
+
+    pub fn sudden_death(time: Duration) -> Self {
+        return Self::with_increment(time, Increment::None);
+    }
+
+    pub fn fischer(time: Duration, increment: Duration) -> Self {
+        return Self::with_increment(time, Increment::Fischer(increment));
+    }
+
+    pub fn bronstein(time: Duration, delay: Duration) -> Self {
+        return Self::with_increment(time, Increment::Bronstein(delay));
+    }
+
+    pub fn simple_delay(time: Duration, delay: Duration) -> Self {
+        return Self::with_increment(time, Increment::SimpleDelay(delay));
+    }
+
+    fn with_increment(time: Duration, increment: Increment) -> Self {
+        return Self { stages: vec![TimeStage { moves: None, time, increment }] };
+    }
+}
+
+// Written as in the PGN TimeControl tag, ex "40/5400+30:1800+30". Delays can not be written in PGN
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut stages: Vec<String> = Vec::new();
+        for stage in &self.stages {
+            let mut text = String::new();
+            if stage.moves.is_some() {
+                text += format!("{}/", stage.moves.unwrap()).as_str();
+            }
+            text += stage.time.as_secs().to_string().as_str();
+            if let Increment::Fischer(increment) = stage.increment {
+                text += format!("+{}", increment.as_secs()).as_str();
+            }
+            stages.push(text);
+        }
+        return write!(f, "{}", stages.join(":"));
+    }
+}
+
+impl FromStr for TimeControl {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<TimeControl, String> {
+        let parse_seconds = |seconds: &str| -> Result<Duration, String> {
+            return match seconds.trim().parse::<u64>() {
+                Ok(seconds) => Ok(Duration::from_secs(seconds)),
+                Err(_) => Err(format!("Invalid number of seconds '{}'", seconds)),
+            };
+        };
+        let mut stages: Vec<TimeStage> = Vec::new();
+        for stage_text in text.split(':') {
+            let (moves, rest) = match stage_text.split_once('/') {
+                Some((moves, rest)) => match moves.trim().parse::<u32>() {
+                    Ok(moves) if moves > 0 => (Some(moves), rest),
+                    _ => return Err(format!("Invalid number of moves '{}'", moves)),
+                },
+                None => (None, stage_text),
+            };
+            let (time, increment) = match rest.split_once('+') {
+                Some((time, increment)) => (parse_seconds(time)?, Increment::Fischer(parse_seconds(increment)?)),
+                None => (parse_seconds(rest)?, Increment::None),
+            };
+            stages.push(TimeStage { moves, time, increment });
+        }
+        return TimeControl::new(stages);
+    }
+}
+
+// A chess clock for both players
+pub struct Clock {
+    control: TimeControl,
+    time_source: Box<dyn TimeSource + Send>,
+    remaining: [Duration; 2], // Time left at the start of the current move, white first
+    stage: [usize; 2],
+    moves_in_stage: [u32; 2],
+    running: Option<ChessPieceColor>,
+    move_started: Duration, // When the running player's move started
+    flagged: Option<ChessPieceColor>,
+}
+
+impl Clock {
+    pub fn new(control: TimeControl, time_source: Box<dyn TimeSource + Send>) -> Self {
+        let time = control.stages[0].time;
+        return Self {
+            control,
+            time_source,
+            remaining: [time, time],
+            stage: [0, 0],
+            moves_in_stage: [0, 0],
+            running: None,
+            move_started: Duration::ZERO,
+            flagged: None,
+        };
+    }
+
+    // Create a clock that uses the system time
+    pub fn with_system_time(control: TimeControl) -> Self {
+        return Self::new(control, Box::new(SystemTimeSource::new()));
+    }
+
+    pub fn time_control(&self) -> &TimeControl {
+        return &self.control;
+    }
+
+    // Start or resume the clock of a player
+    pub fn start(&mut self, color: ChessPieceColor) {
+        if self.flagged.is_some() {
+            return;
+        }
+        self.stop();
+        self.running = Some(color);
+        self.move_started = self.time_source.now();
+    }
+
+    // Stop the clock, ex when the game is paused or over. The time used so far is kept
+    pub fn stop(&mut self) {
+        if self.running.is_none() {
+            return;
+        }
+        let color = self.running.unwrap();
+        self.remaining[Self::get_index(color)] = self.remaining(color);
+        self.running = None;
+    }
+
+    pub fn running(&self) -> Option<ChessPieceColor> {
+        return self.running;
+    }
+
+    // Get the time left for a player, including the time used on the current move
+    pub fn remaining(&self, color: ChessPieceColor) -> Duration {
+        let remaining = self.remaining[Self::get_index(color)];
+        if self.running != Some(color) {
+            return remaining;
+        }
+        let mut used = self.time_source.now().saturating_sub(self.move_started);
+        if let Increment::SimpleDelay(delay) = self.get_stage(color).increment {
+            used = used.saturating_sub(delay);
+        }
+        return remaining.saturating_sub(used);
+    }
+
+    // End the move of the running player and start the clock of the other player
+    pub fn press(&mut self) -> Result<(), String> {
+        if self.running.is_none() {
+            return Err("The clock is not running".to_string());
+        }
+        let color = self.running.unwrap();
+        if self.flag_fall().is_some() {
+            self.flagged = Some(color);
+            self.stop();
+            return Err(format!("{:?} has run out of time", color));
+        }
+
+        let used = self.time_source.now().saturating_sub(self.move_started);
+        let mut remaining = self.remaining(color);
+        match self.get_stage(color).increment {
+            Increment::Fischer(increment) => remaining += increment,
+            Increment::Bronstein(delay) => remaining += used.min(delay),
+            Increment::None | Increment::SimpleDelay(_) => {}
+        }
+
+        // Add the time of the next stage when the moves of this stage are done, the last stage repeats
+        let index = Self::get_index(color);
+        self.moves_in_stage[index] += 1;
+        if Some(self.moves_in_stage[index]) == self.get_stage(color).moves {
+            self.stage[index] = (self.stage[index] + 1).min(self.control.stages.len() - 1);
+            self.moves_in_stage[index] = 0;
+            remaining += self.get_stage(color).time;
+        }
+        self.remaining[index] = remaining;
+        self.running = None;
+
+        let other_color = match color {
+            ChessPieceColor::White => ChessPieceColor::Black,
+            ChessPieceColor::Black => ChessPieceColor::White,
+        };
+        self.start(other_color);
+        return Ok(());
+    }
+
+    // Get the player who has run out of time, if any
+    pub fn flag_fall(&self) -> Option<ChessPieceColor> {
+        if self.flagged.is_some() {
+            return self.flagged;
+        }
+        if self.running.is_some() && self.remaining(self.running.unwrap()) == Duration::ZERO {
+            return self.running;
+        }
+        return None;
+    }
+
+    // Get the stage of the time control a player is in
+    pub fn get_stage(&self, color: ChessPieceColor) -> TimeStage {
+        return self.control.stages[self.stage[Self::get_index(color)]];
+    }
+
+    fn get_index(color: ChessPieceColor) -> usize {
+        match color {
+            ChessPieceColor::White => return 0,
+            ChessPieceColor::Black => return 1,
+        }
+    }
+}
+
+impl Game {
+    // End the game if a player's time has run out, returns true if the game ended
+    pub fn update_from_clock(&mut self, clock: &Clock) -> bool {
+        if self.flagged.is_some() {
+            return true;
+        }
+        match clock.flag_fall() {
+            Some(color) => {
+                self.set_flag_fall(color);
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    // End the game because a player has run out of time
+    pub fn set_flag_fall(&mut self, color: ChessPieceColor) {
+        self.flagged = Some(color);
+    }
+
+    pub fn flag_fall(&self) -> Option<ChessPieceColor> {
+        return self.flagged;
+    }
+
+    // Returns false if a player can not checkmate even with the worst play from the opponent.
+    // A player with only a king, or a king and one bishop or knight against a lone king, can not checkmate
+    pub fn can_checkmate(&self, color: ChessPieceColor) -> bool {
+        let mut minor_pieces = 0;
+        let mut opponent_pieces = 0;
+        for piece in self.board.iter().flatten() {
+            if piece.id == ChessPieceId::King {
+                continue;
+            }
+            if piece.color != color {
+                opponent_pieces += 1;
+                continue;
+            }
+            match piece.id {
+                ChessPieceId::Bishop | ChessPieceId::Knight => minor_pieces += 1,
+                _ => return true,
+            }
+        }
+        if minor_pieces == 0 {
+            return false;
+        }
+        return minor_pieces > 1 || opponent_pieces > 0;
+    }
+}
@@ -0,0 +1,364 @@
+use std::{fmt, fs, str::FromStr};
+
+use super::{AnsiRenderer, AsciiRenderer, BoardPosition, BoardRenderer, ChessPieceColor, Game, RenderOptions, DEFAULT_SEARCH_DEPTH};
+
+pub const COMMAND_HELP: &str = "\
+Commands:
+  <move>           Play a move in algebraic notation, ex Nf3 or exd5
+  moves <square>   Show the squares the piece on a square can move to
+  undo             Take back the last move, or the last two against the computer
+  fen              Print the position as FEN
+  load <fen>       Start from a position
+  pgn save <file>  Save the game as PGN
+  flip             Show the board from the other side
+  hint             Suggest a move
+  resign           Resign the game for the side to move
+  draw             Offer a draw, accept one or claim one by repetition
+  history          List the moves played
+  eval             Show the evaluation of the position
+  help             Show this help";
+
+// A command typed in the command line front end
+#[derive(Clone, PartialEq, Debug)]
+pub enum Command {
+    Move(String),
+    Moves(BoardPosition),
+    Undo,
+    Fen,
+    Load(String),
+    PgnSave(String),
+    Flip,
+    Hint,
+    Resign,
+    Draw,
+    History,
+    Eval,
+    Help,
+}
+
+// Why a command could not be read or carried out
+#[derive(Clone, PartialEq, Debug)]
+pub enum CommandError {
+    Empty,
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidSquare(String),
+    IllegalMove(String),
+    InvalidFen(String),
+    NothingToUndo,
+    GameOver,
+    WriteFailed(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::Empty => return write!(f, "No command given, type help for a list of commands"),
+            CommandError::UnknownCommand(name) => return write!(f, "Unknown command '{}', type help for a list of commands", name),
+            CommandError::MissingArgument(argument) => return write!(f, "Missing argument: {}", argument),
+            CommandError::InvalidSquare(text) => return write!(f, "Invalid square '{}'", text),
+            CommandError::IllegalMove(error) => return write!(f, "Move failed: {}", error),
+            CommandError::InvalidFen(error) => return write!(f, "Invalid FEN: {}", error),
+            CommandError::NothingToUndo => return write!(f, "There is no move to undo"),
+            CommandError::GameOver => return write!(f, "The game is over"),
+            CommandError::WriteFailed(error) => return write!(f, "Could not write file: {}", error),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    fn from_str(text: &str) -> Result<Command, CommandError> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        if words.is_empty() {
+            return Err(CommandError::Empty);
+        }
+        let argument = words.get(1).copied();
+        let command = match words[0] {
+            "moves" => {
+                let square = argument.ok_or(CommandError::MissingArgument("square"))?;
+                let pos = BoardPosition::from_algebraic_notation(square)
+                    .map_err(|_| CommandError::InvalidSquare(square.to_string()))?;
+                Command::Moves(pos)
+            }
+            "undo" => Command::Undo,
+            "fen" => Command::Fen,
+            // The FEN fields are separated by spaces, so use the rest of the line
+            "load" if words.len() > 1 => Command::Load(words[1..].join(" ")),
+            "load" => return Err(CommandError::MissingArgument("FEN")),
+            "pgn" => match (argument, words.get(2)) {
+                (Some("save"), Some(path)) => Command::PgnSave(path.to_string()),
+                (Some("save"), None) => return Err(CommandError::MissingArgument("file")),
+                _ => return Err(CommandError::UnknownCommand(words[..words.len().min(2)].join(" "))),
+            },
+            "flip" => Command::Flip,
+            "hint" => Command::Hint,
+            "resign" => Command::Resign,
+            "draw" => Command::Draw,
+            "history" => Command::History,
+            "eval" => Command::Eval,
+            "help" => Command::Help,
+            // Anything else of one word is taken as a move
+            name if words.len() == 1 => Command::Move(name.to_string()),
+            name => return Err(CommandError::UnknownCommand(name.to_string())),
+        };
+        return Ok(command);
+    }
+}
+
+// How a game ended other than on the board
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SessionResult {
+    Resigned(ChessPieceColor),
+    DrawAgreed,
+    DrawClaimed,
+}
+
+// A game played through commands, keeping what the front end shows between commands
+pub struct CommandSession {
+    pub game: Game,
+    pub computer: Option<ChessPieceColor>,
+    pub orientation: Option<ChessPieceColor>, // None to show the board from the side to move
+    pub colored: bool, // Draw the board with ANSI colors instead of plain text
+    pub result: Option<SessionResult>,
+    draw_offer: Option<ChessPieceColor>,
+}
+
+fn get_opponent(color: ChessPieceColor) -> ChessPieceColor {
+    match color {
+        ChessPieceColor::White => return ChessPieceColor::Black,
+        ChessPieceColor::Black => return ChessPieceColor::White,
+    }
+}
+
+impl CommandSession {
+    pub fn new(game: Game) -> Self {
+        return Self {
+            game,
+            computer: None,
+            orientation: None,
+            colored: true,
+            result: None,
+            draw_offer: None,
+        };
+    }
+
+    pub fn is_over(&self) -> bool {
+        return self.result.is_some() || self.game.game_is_over();
+    }
+
+    // Read and carry out a line of input, returning the text to show
+    pub fn execute_line(&mut self, line: &str) -> Result<String, CommandError> {
+        let command: Command = line.parse()?;
+        return self.execute(command);
+    }
+
+    // Run commands from a script, one on each line. Empty lines and lines starting with '#' are skipped.
+    // Each command is echoed after "> " followed by its output, so a run can be compared with an earlier one
+    pub fn run_script(&mut self, script: &str) -> String {
+        let mut out = String::new();
+        for line in script.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            out += &format!("> {}\n", line);
+            match self.execute_line(line) {
+                Ok(text) => out += &text,
+                Err(error) => out += &format!("Error: {}\n", error),
+            }
+        }
+        return out;
+    }
+
+    // Carry out a command, returning the text to show
+    pub fn execute(&mut self, command: Command) -> Result<String, CommandError> {
+        let mut out = String::new();
+        match command {
+            Command::Move(text) => {
+                if self.is_over() {
+                    return Err(CommandError::GameOver);
+                }
+                let chess_move = self.game.parse_san(&text).map_err(CommandError::IllegalMove)?;
+                let san = self.game.move_to_san(&chess_move);
+                let color = self.game.turn;
+                self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece)
+                    .map_err(CommandError::IllegalMove)?;
+                // A draw offer stands while the player who made it moves, the opponent declines it by moving
+                if self.draw_offer != Some(color) {
+                    self.draw_offer = None;
+                }
+                out += &format!("Played {}\n", san);
+                out += &self.play_computer_move();
+                out += &self.get_game_info();
+            }
+            Command::Moves(pos) => {
+                let highlights: Vec<BoardPosition> = self.game.legal_moves().iter()
+                    .filter(|legal_move| legal_move.board_move.from_pos == pos)
+                    .map(|legal_move| legal_move.board_move.to_pos)
+                    .collect();
+                let options = RenderOptions { selected: Some(pos), highlights, ..self.get_render_options() };
+                out += &self.render(&options);
+            }
+            Command::Undo => {
+                let mut undone = Vec::new();
+                while let Ok(entry) = self.game.undo_move() {
+                    undone.push(entry.san);
+                    if self.computer != Some(self.game.turn) {
+                        break;
+                    }
+                }
+                if undone.is_empty() {
+                    return Err(CommandError::NothingToUndo);
+                }
+                self.result = None;
+                self.draw_offer = None;
+                out += &format!("Took back {}\n", undone.join(", "));
+                out += &self.get_game_info();
+            }
+            Command::Fen => out += &format!("{}\n", self.game.to_fen()),
+            Command::Load(fen) => {
+                self.game = Game::from_fen(&fen).map_err(CommandError::InvalidFen)?;
+                self.result = None;
+                self.draw_offer = None;
+                out += &self.play_computer_move();
+                out += &self.get_game_info();
+            }
+            Command::PgnSave(path) => {
+                fs::write(&path, self.game.to_pgn()).map_err(|error| CommandError::WriteFailed(error.to_string()))?;
+                out += &format!("Saved {}\n", path);
+            }
+            Command::Flip => {
+                self.orientation = Some(get_opponent(self.get_orientation()));
+                out += &self.render(&self.get_render_options());
+            }
+            Command::Hint => {
+                if self.is_over() {
+                    return Err(CommandError::GameOver);
+                }
+                let chess_move = self.game.best_move(DEFAULT_SEARCH_DEPTH).ok_or(CommandError::GameOver)?;
+                out += &format!("Hint: {}\n", self.game.move_to_san(&chess_move));
+            }
+            Command::Resign => {
+                if self.is_over() {
+                    return Err(CommandError::GameOver);
+                }
+                self.result = Some(SessionResult::Resigned(self.game.turn));
+                out += &self.get_status();
+            }
+            Command::Draw => {
+                if self.is_over() {
+                    return Err(CommandError::GameOver);
+                }
+                out += &self.draw();
+            }
+            Command::History => {
+                let history = self.game.history();
+                if history.is_empty() {
+                    out += "No moves played\n";
+                }
+                // Number the moves as in PGN, starting from the move number of the start position
+                let start = self.game.start_position();
+                let mut move_number = start.fullmove_number();
+                let mut turn = start.turn;
+                for (index, entry) in history.iter().enumerate() {
+                    if turn == ChessPieceColor::White {
+                        out += &format!("{}. {}", move_number, entry.san);
+                    }
+                    else {
+                        if index == 0 {
+                            out += &format!("{}...", move_number);
+                        }
+                        out += &format!(" {}\n", entry.san);
+                        move_number += 1;
+                    }
+                    turn = get_opponent(turn);
+                }
+                if turn == ChessPieceColor::Black {
+                    out += "\n";
+                }
+            }
+            Command::Eval => {
+                // Shown in pawns from white's side, as is usual
+                let mut score = self.game.evaluate();
+                if self.game.turn == ChessPieceColor::Black {
+                    score = -score;
+                }
+                out += &format!("Evaluation: {:+.2}\n", score as f32 / 100.0);
+            }
+            Command::Help => out += &format!("{}\n", COMMAND_HELP),
+        }
+        return Ok(out);
+    }
+
+    // Offer a draw, accept the opponent's offer, or claim a draw by repetition
+    fn draw(&mut self) -> String {
+        let color = self.game.turn;
+        if self.game.is_threefold_repetition() {
+            self.result = Some(SessionResult::DrawClaimed);
+            return self.get_status();
+        }
+        if self.draw_offer == Some(get_opponent(color)) {
+            self.result = Some(SessionResult::DrawAgreed);
+            return self.get_status();
+        }
+        if self.computer == Some(get_opponent(color)) {
+            // The computer accepts when it does not think it is better
+            if self.game.evaluate() >= 0 {
+                self.result = Some(SessionResult::DrawAgreed);
+                return self.get_status();
+            }
+            return "The computer declines the draw offer\n".to_string();
+        }
+        self.draw_offer = Some(color);
+        return format!("{:?} offers a draw, {:?} can accept with draw or decline by moving\n", color, get_opponent(color));
+    }
+
+    // Let the computer move if it is its turn
+    fn play_computer_move(&mut self) -> String {
+        if self.computer != Some(self.game.turn) || self.is_over() {
+            return String::new();
+        }
+        let chess_move = self.game.best_move(DEFAULT_SEARCH_DEPTH).unwrap();
+        let san = self.game.move_to_san(&chess_move);
+        self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece).unwrap();
+        return format!("Computer plays {}\n", san);
+    }
+
+    fn get_orientation(&self) -> ChessPieceColor {
+        return self.orientation.unwrap_or(self.game.turn);
+    }
+
+    fn get_render_options(&self) -> RenderOptions {
+        return RenderOptions { orientation: self.get_orientation(), ..RenderOptions::default() };
+    }
+
+    fn render(&self, options: &RenderOptions) -> String {
+        if self.colored {
+            return AnsiRenderer.render_to_string(&self.game, options);
+        }
+        return AsciiRenderer.render_to_string(&self.game, options);
+    }
+
+    // Get the board followed by whose turn it is or how the game ended
+    pub fn get_game_info(&self) -> String {
+        return self.render(&self.get_render_options()) + &self.get_status();
+    }
+
+    pub fn get_status(&self) -> String {
+        match self.result {
+            Some(SessionResult::Resigned(color)) => return format!("{:?} resigns, {:?} wins!\n", color, get_opponent(color)),
+            Some(SessionResult::DrawAgreed) => return "Draw agreed\n".to_string(),
+            Some(SessionResult::DrawClaimed) => return "Draw by threefold repetition\n".to_string(),
+            None => {}
+        }
+        if self.game.game_is_over() {
+            return match self.game.get_winner() {
+                Some(color) => format!("Game is over!\n{:?} wins!\n", color),
+                None => "Game is over!\nDraw\n".to_string(),
+            };
+        }
+        let check = if self.game.is_check().is_some() { "Check!\n" } else { "" };
+        return format!("{:?}'s turn\n{}", self.game.turn, check);
+    }
+}
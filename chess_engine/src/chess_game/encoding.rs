@@ -0,0 +1,221 @@
+use super::{BoardMove, BoardPosition, CastleSide, CastlingRights, ChessPieceColor, ChessPieceId, Game, Move, PositionBuilder};
+
+// Size of a packed position, 32 bytes for the board and 5 bytes for turn, castling, en passant and move clocks
+pub const PACKED_POSITION_SIZE: usize = 37;
+
+// Flags stored in the two highest bits of an encoded move
+pub const MOVE_FLAG_PROMOTION: u16 = 1 << 14;
+pub const MOVE_FLAG_EN_PASSANT: u16 = 2 << 14;
+pub const MOVE_FLAG_CASTLE: u16 = 3 << 14;
+
+const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+// Get the index of a square from 0 for a1 to 63 for h8
+fn get_square_index(pos: BoardPosition) -> u16 {
+    return (pos.x & 7) as u16 + 8 * (7 - (pos.y & 7)) as u16;
+}
+
+fn get_square_pos(index: u16) -> BoardPosition {
+    return BoardPosition::new((index % 8) as u8, 7 - (index / 8) as u8);
+}
+
+impl BoardMove {
+    // Encode the move in 16 bits, the from square in bits 0-5 and the to square in bits 6-11
+    pub fn encode(&self) -> u16 {
+        return Move::new(*self, None).encode();
+    }
+
+    // Decode a move encoded by BoardMove::encode or Move::encode, promotion and flags are ignored
+    pub fn decode(code: u16) -> BoardMove {
+        return Move::decode(code).board_move;
+    }
+}
+
+impl Move {
+    // Encode the move in 16 bits: from square in bits 0-5, to square in bits 6-11,
+    // promotion piece in bits 12-13 and flags in bits 14-15
+    pub fn encode(&self) -> u16 {
+        let mut code = get_square_index(self.board_move.from_pos) | get_square_index(self.board_move.to_pos) << 6;
+        if self.promote_piece.is_some() {
+            let promote_bits = match self.promote_piece.unwrap() {
+                ChessPieceId::Knight => 0,
+                ChessPieceId::Bishop => 1,
+                ChessPieceId::Rook => 2,
+                _ => 3,
+            };
+            code |= promote_bits << 12 | MOVE_FLAG_PROMOTION;
+        }
+        return code;
+    }
+
+    pub fn decode(code: u16) -> Move {
+        let from_pos = get_square_pos(code & 63);
+        let to_pos = get_square_pos(code >> 6 & 63);
+        let mut promote_piece = None;
+        if code & MOVE_FLAG_CASTLE == MOVE_FLAG_PROMOTION {
+            promote_piece = match code >> 12 & 3 {
+                0 => Some(ChessPieceId::Knight),
+                1 => Some(ChessPieceId::Bishop),
+                2 => Some(ChessPieceId::Rook),
+                _ => Some(ChessPieceId::Queen),
+            };
+        }
+        return Move::new(BoardMove::new(from_pos.x, from_pos.y, to_pos.x, to_pos.y), promote_piece);
+    }
+}
+
+impl Game {
+    // Encode a move in 16 bits like Move::encode, with the en passant or castling flag set if the move is one
+    pub fn encode_move(&self, chess_move: &Move) -> u16 {
+        let code = chess_move.encode();
+        let board_move = chess_move.board_move;
+        let piece = self.piece_at(board_move.from_pos);
+        if piece.is_none() || chess_move.promote_piece.is_some() {
+            return code;
+        }
+        let d_x = board_move.to_pos.x as i32 - board_move.from_pos.x as i32;
+        if piece.unwrap().id == ChessPieceId::King && d_x.abs() == 2 {
+            return code | MOVE_FLAG_CASTLE;
+        }
+        if piece.unwrap().id == ChessPieceId::Pawn && d_x != 0 && self.piece_at(board_move.to_pos).is_none() {
+            return code | MOVE_FLAG_EN_PASSANT;
+        }
+        return code;
+    }
+
+    // Pack the position in 37 bytes: 4 bits for each square from a1 to h8, then turn and castling rights,
+    // en passant file, halfmove clock and fullmove number. The move history is not included
+    pub fn encode_position(&self) -> [u8; PACKED_POSITION_SIZE] {
+        let mut bytes = [0; PACKED_POSITION_SIZE];
+        for index in 0..64 {
+            let piece = self.piece_at(get_square_pos(index));
+            if piece.is_none() {
+                continue;
+            }
+            let mut nibble = match piece.unwrap().id {
+                ChessPieceId::Pawn => 1,
+                ChessPieceId::Knight => 2,
+                ChessPieceId::Bishop => 3,
+                ChessPieceId::Rook => 4,
+                ChessPieceId::Queen => 5,
+                ChessPieceId::King => 6,
+            };
+            if piece.unwrap().color == ChessPieceColor::Black {
+                nibble += 8;
+            }
+            bytes[index as usize / 2] |= nibble << (4 * (index % 2));
+        }
+        bytes[32] = self.castling_rights().bits() << 1;
+        if self.turn == ChessPieceColor::Black {
+            bytes[32] |= 1;
+        }
+        if self.en_passant_square().is_some() {
+            bytes[33] = self.en_passant_square().unwrap().x + 1;
+        }
+        bytes[34] = self.halfmove_clock().min(255) as u8;
+        bytes[35..37].copy_from_slice(&(self.fullmove_number().min(u16::MAX as u32) as u16).to_le_bytes());
+        return bytes;
+    }
+
+    // Create a game from a position packed by Game::encode_position. The position is not validated again,
+    // since it can be a game built by hand that PositionBuilder would reject
+    pub fn decode_position(bytes: &[u8]) -> Result<Game, String> {
+        if bytes.len() != PACKED_POSITION_SIZE {
+            return Err(format!("A packed position is {} bytes", PACKED_POSITION_SIZE));
+        }
+        let mut builder = PositionBuilder::new();
+        for index in 0..64 {
+            let nibble = bytes[index as usize / 2] >> (4 * (index % 2)) & 15;
+            if nibble == 0 {
+                continue;
+            }
+            let id = match nibble & 7 {
+                1 => ChessPieceId::Pawn,
+                2 => ChessPieceId::Knight,
+                3 => ChessPieceId::Bishop,
+                4 => ChessPieceId::Rook,
+                5 => ChessPieceId::Queen,
+                6 => ChessPieceId::King,
+                _ => return Err(format!("Unknown piece {} in packed position", nibble)),
+            };
+            let color = if nibble & 8 == 0 { ChessPieceColor::White } else { ChessPieceColor::Black };
+            builder = builder.piece(get_square_pos(index), id, color);
+        }
+
+        let castling_rights = CastlingRights::from_bits(bytes[32] >> 1);
+        for color in [ChessPieceColor::White, ChessPieceColor::Black] {
+            builder = builder.castling_rights(
+                color,
+                castling_rights.has(color, CastleSide::KingSide),
+                castling_rights.has(color, CastleSide::QueenSide),
+            );
+        }
+        let turn = if bytes[32] & 1 == 0 { ChessPieceColor::White } else { ChessPieceColor::Black };
+        builder = builder.turn(turn);
+        if bytes[33] > 8 {
+            return Err("Invalid en passant file in packed position".to_string());
+        }
+        if bytes[33] > 0 {
+            // The square passed is on the third rank for white pawns and the sixth rank for black pawns
+            let y = if turn == ChessPieceColor::White { 2 } else { 5 };
+            builder = builder.en_passant(Some(BoardPosition::new(bytes[33] - 1, y)));
+        }
+        builder = builder
+            .halfmove_clock(bytes[34] as u32)
+            .fullmove_number(u16::from_le_bytes([bytes[35], bytes[36]]) as u32);
+        return Ok(builder.build_unchecked());
+    }
+
+    // Encode the whole game with one byte for each move, the index of the move in Game::legal_moves.
+    // The first byte is 0 for games from the normal start position, or 1 followed by the packed start position
+    pub fn encode_game(&self) -> Result<Vec<u8>, String> {
+        let mut game = self.start_position();
+        let start_fen = game.to_fen();
+        let mut bytes: Vec<u8> = Vec::new();
+        if start_fen == START_FEN {
+            bytes.push(0);
+        }
+        else {
+            bytes.push(1);
+            bytes.extend_from_slice(&game.encode_position());
+        }
+
+        for entry in self.history() {
+            let mut chess_move = entry.chess_move;
+            if game.will_require_promotion(chess_move.board_move) && chess_move.promote_piece.is_none() {
+                chess_move.promote_piece = Some(ChessPieceId::Queen);
+            }
+            let code = chess_move.encode();
+            let index = game.legal_moves().iter().position(|legal_move| legal_move.encode() == code);
+            if index.is_none() {
+                return Err(format!("{} is not a legal move", entry.san));
+            }
+            bytes.push(index.unwrap() as u8);
+            game.move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+        }
+        return Ok(bytes);
+    }
+
+    // Create a game from bytes encoded by Game::encode_game, with all its moves in the history
+    pub fn decode_game(bytes: &[u8]) -> Result<Game, String> {
+        if bytes.is_empty() {
+            return Err("No data".to_string());
+        }
+        let (mut game, moves) = match bytes[0] {
+            0 => (Game::from_fen(START_FEN)?, &bytes[1..]),
+            1 if bytes.len() > PACKED_POSITION_SIZE => {
+                (Game::decode_position(&bytes[1..PACKED_POSITION_SIZE + 1])?, &bytes[PACKED_POSITION_SIZE + 1..])
+            }
+            _ => return Err("Invalid start of encoded game".to_string()),
+        };
+        for &index in moves {
+            let legal_moves = game.legal_moves();
+            if index as usize >= legal_moves.len() {
+                return Err(format!("Move index {} is out of range", index));
+            }
+            let chess_move = legal_moves[index as usize];
+            game.move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+        }
+        return Ok(game);
+    }
+}
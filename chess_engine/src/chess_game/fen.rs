@@ -0,0 +1,170 @@
+use super::{BoardPosition, CastleSide, ChessPieceColor, Game, NotationLocale, PositionBuilder, PositionError};
+
+impl Game {
+    // Get the position in Forsyth-Edwards Notation, ex "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+    pub fn to_fen(&self) -> String {
+        let mut fen = self.get_fen_position();
+        fen += format!(" {} {}", self.halfmove_clock(), self.fullmove_number()).as_str();
+        return fen;
+    }
+
+    // Create a game from Forsyth-Edwards Notation, the move clocks at the end may be left out
+    pub fn from_fen(fen: &str) -> Result<Game, String> {
+        return Self::get_fen_builder(fen)?.build().map_err(|errors| PositionError::join(&errors));
+    }
+
+    // Create a game from FEN written by to_fen, without making sure the position is valid
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_fen_unchecked(fen: &str) -> Result<Game, String> {
+        return Ok(Self::get_fen_builder(fen)?.build_unchecked());
+    }
+
+    // Read the fields of a FEN into a position builder
+    fn get_fen_builder(fen: &str) -> Result<PositionBuilder, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() != 4 && fields.len() != 6 {
+            return Err("FEN needs 4 or 6 fields".to_string());
+        }
+        let mut builder = PositionBuilder::new();
+
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err("FEN needs 8 ranks".to_string());
+        }
+        for (y, rank) in ranks.iter().enumerate() {
+            let mut x = 0;
+            for letter in rank.chars() {
+                let width = letter.to_digit(10).unwrap_or(1);
+                if x + width > 8 {
+                    return Err(format!("Rank {} in FEN has more than 8 squares", 8 - y));
+                }
+                if letter.is_ascii_digit() {
+                    x += width;
+                    continue;
+                }
+                let id = NotationLocale::English.get_piece_type(letter.to_ascii_uppercase());
+                if id.is_err() {
+                    return Err(format!("Unknown piece '{}' in FEN", letter));
+                }
+                let color = if letter.is_ascii_uppercase() { ChessPieceColor::White } else { ChessPieceColor::Black };
+                builder = builder.piece(BoardPosition::new(x as u8, y as u8), id.unwrap(), color);
+                x += 1;
+            }
+            if x != 8 {
+                return Err(format!("Rank {} in FEN does not have 8 squares", 8 - y));
+            }
+        }
+
+        match fields[1] {
+            "w" => builder = builder.turn(ChessPieceColor::White),
+            "b" => builder = builder.turn(ChessPieceColor::Black),
+            _ => return Err(format!("Unknown color '{}' in FEN", fields[1])),
+        }
+
+        if fields[2] != "-" {
+            if fields[2].chars().any(|letter| !"KQkq".contains(letter)) {
+                return Err(format!("Unknown castling rights '{}' in FEN", fields[2]));
+            }
+            builder = builder
+                .castling_rights(ChessPieceColor::White, fields[2].contains('K'), fields[2].contains('Q'))
+                .castling_rights(ChessPieceColor::Black, fields[2].contains('k'), fields[2].contains('q'));
+        }
+
+        if fields[3] != "-" {
+            builder = builder.en_passant(Some(BoardPosition::from_algebraic_notation(fields[3])?));
+        }
+
+        if fields.len() == 6 {
+            match (fields[4].parse::<u32>(), fields[5].parse::<u32>()) {
+                (Ok(halfmove_clock), Ok(fullmove_number)) => {
+                    builder = builder.halfmove_clock(halfmove_clock).fullmove_number(fullmove_number);
+                }
+                _ => return Err("Invalid move clocks in FEN".to_string()),
+            }
+        }
+
+        return Ok(builder);
+    }
+
+    // Get a hash of the position, positions with the same pieces, turn, castling rights and en passant square are equal.
+    // It is the 64 bit FNV-1a hash of the first four FEN fields, so stored hashes stay valid between Rust versions
+    pub fn position_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.get_fen_position().bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        return hash;
+    }
+
+    // Get the square a pawn passed when it moved two squares on the last move
+    pub fn en_passant_square(&self) -> Option<BoardPosition> {
+        if !self.last_move_passant || self.last_move.is_none() {
+            return None;
+        }
+        let last_move = self.last_move.unwrap();
+        return Some(BoardPosition::new(last_move.to_pos.x, (last_move.from_pos.y + last_move.to_pos.y) / 2));
+    }
+
+    // Get the first four fields of the FEN, without the move clocks
+    fn get_fen_position(&self) -> String {
+        let mut fen = String::new();
+        for y in 0..8 {
+            let mut empty_squares = 0;
+            for x in 0..8 {
+                let piece = self.piece_at(BoardPosition::new(x, y));
+                if piece.is_none() {
+                    empty_squares += 1;
+                    continue;
+                }
+                if empty_squares > 0 {
+                    fen += empty_squares.to_string().as_str();
+                    empty_squares = 0;
+                }
+                let letter = piece.unwrap().id.to_uci_letter();
+                if piece.unwrap().color == ChessPieceColor::White {
+                    fen.push(letter.to_ascii_uppercase());
+                }
+                else {
+                    fen.push(letter);
+                }
+            }
+            if empty_squares > 0 {
+                fen += empty_squares.to_string().as_str();
+            }
+            if y < 7 {
+                fen.push('/');
+            }
+        }
+
+        if self.turn == ChessPieceColor::White {
+            fen += " w ";
+        }
+        else {
+            fen += " b ";
+        }
+
+        let castling_rights = self.castling_rights();
+        if castling_rights.has(ChessPieceColor::White, CastleSide::KingSide) {
+            fen.push('K');
+        }
+        if castling_rights.has(ChessPieceColor::White, CastleSide::QueenSide) {
+            fen.push('Q');
+        }
+        if castling_rights.has(ChessPieceColor::Black, CastleSide::KingSide) {
+            fen.push('k');
+        }
+        if castling_rights.has(ChessPieceColor::Black, CastleSide::QueenSide) {
+            fen.push('q');
+        }
+        if castling_rights.is_empty() {
+            fen.push('-');
+        }
+
+        match self.en_passant_square() {
+            Some(pos) => fen += format!(" {}", pos.to_algebraic_notation()).as_str(),
+            None => fen += " -",
+        }
+        return fen;
+    }
+}
@@ -0,0 +1,219 @@
+// C interface, declared in include/chess_engine.h. Pointers passed in must be null or valid as described there
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use super::{ChessPieceColor, Game, Move};
+
+// Size of a move in UCI notation with its terminating zero, ex "e7e8q"
+pub const CHESS_UCI_MOVE_SIZE: usize = 6;
+
+// Error codes, the same as ChessError in the header
+pub const CHESS_OK: c_int = 0;
+pub const CHESS_ERROR_NULL_POINTER: c_int = 1;
+pub const CHESS_ERROR_INVALID_UTF8: c_int = 2;
+pub const CHESS_ERROR_INVALID_FEN: c_int = 3;
+pub const CHESS_ERROR_INVALID_MOVE_TEXT: c_int = 4;
+pub const CHESS_ERROR_NO_PIECE: c_int = 5;
+pub const CHESS_ERROR_NOT_YOUR_PIECE: c_int = 6;
+pub const CHESS_ERROR_OWN_PIECE_ON_TARGET: c_int = 7;
+pub const CHESS_ERROR_LEAVES_KING_IN_CHECK: c_int = 8;
+pub const CHESS_ERROR_PROMOTION: c_int = 9;
+pub const CHESS_ERROR_GAME_OVER: c_int = 10;
+pub const CHESS_ERROR_ILLEGAL_MOVE: c_int = 11;
+pub const CHESS_ERROR_BUFFER_TOO_SMALL: c_int = 12;
+pub const CHESS_ERROR_INTERNAL: c_int = 13;
+
+// Outcomes, the same as ChessOutcome in the header
+pub const CHESS_OUTCOME_ONGOING: c_int = 0;
+pub const CHESS_OUTCOME_WHITE_WINS: c_int = 1;
+pub const CHESS_OUTCOME_BLACK_WINS: c_int = 2;
+pub const CHESS_OUTCOME_DRAW: c_int = 3;
+
+// A game behind an opaque pointer, with the message of the last error so C can show it
+pub struct FfiGame {
+    game: Game,
+    last_error: CString,
+}
+
+// Get the error code for a message from Game::move_piece. Messages not listed are pieces moving in ways they can not
+pub fn get_move_error_code(message: &str) -> c_int {
+    match message {
+        "No piece on square selected!" => return CHESS_ERROR_NO_PIECE,
+        "Cannot move opponents pieces" => return CHESS_ERROR_NOT_YOUR_PIECE,
+        "Cannot move piece to piece of same color" => return CHESS_ERROR_OWN_PIECE_ON_TARGET,
+        "Move leads to check!" => return CHESS_ERROR_LEAVES_KING_IN_CHECK,
+        "The game is over, time has run out" | "Maximum move count reached" => return CHESS_ERROR_GAME_OVER,
+        "Move requires promotion, cannot promote to pawn or king" | "Cannot promote piece" => return CHESS_ERROR_PROMOTION,
+        _ => return CHESS_ERROR_ILLEGAL_MOVE,
+    }
+}
+
+impl FfiGame {
+    // Remember the message of an error and return its code
+    fn fail(&mut self, code: c_int, message: &str) -> c_int {
+        // Messages never contain zeros, but make sure the string can be made anyway
+        self.last_error = CString::new(message.replace('\0', "")).unwrap();
+        return code;
+    }
+
+    fn load_fen(&mut self, fen: &str) -> c_int {
+        match Game::from_fen(fen) {
+            Ok(game) => {
+                self.game = game;
+                return CHESS_OK;
+            }
+            Err(error) => return self.fail(CHESS_ERROR_INVALID_FEN, &error),
+        }
+    }
+
+    fn make_move(&mut self, text: &str) -> c_int {
+        let chess_move = match Move::from_uci(text) {
+            Ok(chess_move) => chess_move,
+            Err(error) => return self.fail(CHESS_ERROR_INVALID_MOVE_TEXT, &error),
+        };
+        if self.game.game_is_over() {
+            return self.fail(CHESS_ERROR_GAME_OVER, "The game is over");
+        }
+        match self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece) {
+            Ok(()) => return CHESS_OK,
+            Err(error) => return self.fail(get_move_error_code(&error), &error),
+        }
+    }
+}
+
+// Read a C string, or get the error code for it
+unsafe fn get_str<'a>(text: *const c_char) -> Result<&'a str, c_int> {
+    if text.is_null() {
+        return Err(CHESS_ERROR_NULL_POINTER);
+    }
+    return CStr::from_ptr(text).to_str().map_err(|_| CHESS_ERROR_INVALID_UTF8);
+}
+
+// Copy text with a terminating zero into a buffer of a size
+unsafe fn write_str(text: &str, buffer: *mut c_char, size: usize) -> c_int {
+    if buffer.is_null() {
+        return CHESS_ERROR_NULL_POINTER;
+    }
+    if text.len() + 1 > size {
+        return CHESS_ERROR_BUFFER_TOO_SMALL;
+    }
+    ptr::copy_nonoverlapping(text.as_ptr() as *const c_char, buffer, text.len());
+    *buffer.add(text.len()) = 0;
+    return CHESS_OK;
+}
+
+// Run a function, a panic gives the fallback value instead of unwinding into C
+fn catch_panic<T>(fallback: T, function: impl FnOnce() -> T) -> T {
+    return panic::catch_unwind(AssertUnwindSafe(function)).unwrap_or(fallback);
+}
+
+// Run a function that changes a game, panics are reported as internal errors
+unsafe fn with_game(game: *mut FfiGame, function: impl FnOnce(&mut FfiGame) -> c_int) -> c_int {
+    if game.is_null() {
+        return CHESS_ERROR_NULL_POINTER;
+    }
+    let game = &mut *game;
+    game.last_error = CString::default();
+    let code = catch_panic(None, || Some(function(&mut *game)));
+    if code.is_none() {
+        return game.fail(CHESS_ERROR_INTERNAL, "Internal error");
+    }
+    return code.unwrap();
+}
+
+#[no_mangle]
+pub extern "C" fn chess_game_new() -> *mut FfiGame {
+    let mut game = Game::new();
+    game.set_up_board();
+    return Box::into_raw(Box::new(FfiGame { game, last_error: CString::default() }));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_free(game: *mut FfiGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_load_fen(game: *mut FfiGame, fen: *const c_char) -> c_int {
+    return with_game(game, |game| match get_str(fen) {
+        Ok(fen) => game.load_fen(fen),
+        Err(code) => game.fail(code, "Invalid FEN string"),
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_fen(game: *const FfiGame, buffer: *mut c_char, size: usize) -> c_int {
+    if game.is_null() {
+        return CHESS_ERROR_NULL_POINTER;
+    }
+    return catch_panic(CHESS_ERROR_INTERNAL, || write_str(&(*game).game.to_fen(), buffer, size));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_make_move(game: *mut FfiGame, uci: *const c_char) -> c_int {
+    return with_game(game, |game| match get_str(uci) {
+        Ok(text) => game.make_move(text),
+        Err(code) => game.fail(code, "Invalid move string"),
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_legal_moves(game: *const FfiGame, buffer: *mut c_char, capacity: usize, count: *mut usize) -> c_int {
+    if game.is_null() || count.is_null() {
+        return CHESS_ERROR_NULL_POINTER;
+    }
+    return catch_panic(CHESS_ERROR_INTERNAL, || {
+        let moves = (*game).game.legal_moves();
+        // The count is set even if the buffer is too small, so the caller knows how much room is needed
+        *count = moves.len();
+        if moves.len() > capacity {
+            return CHESS_ERROR_BUFFER_TOO_SMALL;
+        }
+        for (index, chess_move) in moves.iter().enumerate() {
+            let code = write_str(&chess_move.to_uci(), buffer.add(index * CHESS_UCI_MOVE_SIZE), CHESS_UCI_MOVE_SIZE);
+            if code != CHESS_OK {
+                return code;
+            }
+        }
+        return CHESS_OK;
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_outcome(game: *const FfiGame) -> c_int {
+    if game.is_null() {
+        return CHESS_OUTCOME_ONGOING;
+    }
+    let game = &(*game).game;
+    return catch_panic(CHESS_OUTCOME_ONGOING, || match game.get_winner() {
+        Some(ChessPieceColor::White) => CHESS_OUTCOME_WHITE_WINS,
+        Some(ChessPieceColor::Black) => CHESS_OUTCOME_BLACK_WINS,
+        None if game.game_is_over() => CHESS_OUTCOME_DRAW,
+        None => CHESS_OUTCOME_ONGOING,
+    });
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_is_check(game: *const FfiGame) -> c_int {
+    if game.is_null() {
+        return 0;
+    }
+    return catch_panic(0, || (*game).game.is_check().is_some() as c_int);
+}
+
+// The message of the last error, empty after a call that succeeded. Valid until the next call with the game
+#[no_mangle]
+pub unsafe extern "C" fn chess_game_last_error(game: *const FfiGame) -> *const c_char {
+    if game.is_null() {
+        return ptr::null();
+    }
+    return (*game).last_error.as_ptr();
+}
@@ -0,0 +1,159 @@
+use std::time::{Duration, SystemTime};
+
+use std::sync::Arc;
+
+use super::{clock::get_current_time, ChessPiece, ChessPieceId, Game, Move};
+
+// A move that has been played, with information about the position it led to
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoryEntry {
+    pub chess_move: Move,
+    pub san: String,
+    pub fen: String, // Position after the move
+    pub hash: u64, // Position hash after the move, used to find repetitions
+    pub captured: Option<ChessPiece>,
+    pub timestamp: SystemTime,
+    pub clock: Option<Duration>, // Time left on the clock of the player who moved
+    pub comment: Option<String>,
+}
+
+// All moves played in a game, and the game as it was before the first move. The start is kept as a game
+// rather than FEN, so positions set up by hand that FEN validation would refuse can still be played again
+#[derive(Clone, Default)]
+pub(crate) struct MoveHistory {
+    start: Option<Box<Game>>,
+    start_hash: u64,
+    entries: Vec<HistoryEntry>,
+}
+
+impl Game {
+    // Get all moves played since the board was set up
+    pub fn history(&self) -> &[HistoryEntry] {
+        return &self.history.entries;
+    }
+
+    // Get the game as it was before the first move, without any history
+    pub fn start_position(&self) -> Game {
+        if self.history.start.is_some() {
+            return (**self.history.start.as_ref().unwrap()).clone();
+        }
+        let mut game = self.clone();
+        game.clear_history();
+        return game;
+    }
+
+    // Get the position the game started from in FEN
+    pub fn start_fen(&self) -> String {
+        return self.start_position().to_fen();
+    }
+
+    // Set a comment for the last move, ex to export it in PGN
+    pub fn set_last_move_comment(&mut self, comment: Option<String>) -> Result<(), String> {
+        let history = Arc::make_mut(&mut self.history);
+        if history.entries.is_empty() {
+            return Err("No move to comment".to_string());
+        }
+        history.entries.last_mut().unwrap().comment = comment;
+        return Ok(());
+    }
+
+    // Set the time that was left on the clock of the player who did the last move
+    pub fn set_last_move_clock(&mut self, clock: Option<Duration>) -> Result<(), String> {
+        let history = Arc::make_mut(&mut self.history);
+        if history.entries.is_empty() {
+            return Err("No move to set clock time for".to_string());
+        }
+        history.entries.last_mut().unwrap().clock = clock;
+        return Ok(());
+    }
+
+    // Set when the last move was played, ex when a game is loaded
+    #[cfg(feature = "serde")]
+    pub(crate) fn set_last_move_timestamp(&mut self, timestamp: SystemTime) {
+        let history = Arc::make_mut(&mut self.history);
+        if let Some(entry) = history.entries.last_mut() {
+            entry.timestamp = timestamp;
+        }
+    }
+
+    // Get how many times the current position has occurred, including now
+    pub fn repetition_count(&self) -> usize {
+        let entries = self.history();
+        if entries.is_empty() {
+            return 1;
+        }
+        let hash = self.position_hash();
+        let mut count = 0;
+        // Positions before the last capture or pawn move can not be repeated
+        let reversible_moves = (self.halfmove_clock() as usize).min(entries.len());
+        if reversible_moves == entries.len() && self.history.start_hash == hash {
+            count += 1;
+        }
+        for entry in &entries[(entries.len() - reversible_moves).saturating_sub(1)..] {
+            if entry.hash == hash {
+                count += 1;
+            }
+        }
+        return count;
+    }
+
+    pub fn is_threefold_repetition(&self) -> bool {
+        return self.repetition_count() >= 3;
+    }
+
+    // Take back the last move, by playing the game again from the start without it
+    pub fn undo_move(&mut self) -> Result<HistoryEntry, String> {
+        if self.history.entries.is_empty() {
+            return Err("No move to undo".to_string());
+        }
+        let mut entries = self.history.entries.clone();
+        let last_entry = entries.pop().unwrap();
+        let mut game = self.start_position();
+        for entry in &entries {
+            game.try_move_piece(entry.chess_move.board_move, true, entry.chess_move.promote_piece)?;
+        }
+        game.notation_locale = self.notation_locale;
+        game.history = Arc::new(MoveHistory {
+            start: self.history.start.clone(),
+            start_hash: self.history.start_hash,
+            entries,
+        });
+        *self = game;
+        return Ok(last_entry);
+    }
+
+    // Remember a move that was just done, before_move is the game as it was before the move
+    pub(crate) fn record_move(&mut self, before_move: &Game, chess_move: Move) {
+        let board_move = chess_move.board_move;
+        let mut captured = before_move.piece_at(board_move.to_pos);
+        let from_piece = before_move.piece_at(board_move.from_pos);
+        if captured.is_none() && from_piece.is_some() && from_piece.unwrap().id == ChessPieceId::Pawn
+            && board_move.from_pos.x != board_move.to_pos.x && before_move.last_move.is_some() {
+            // En passant
+            captured = before_move.piece_at(before_move.last_move.unwrap().to_pos);
+        }
+        let entry = HistoryEntry {
+            chess_move,
+            san: before_move.move_to_san(&chess_move),
+            fen: self.to_fen(),
+            hash: self.position_hash(),
+            captured,
+            timestamp: get_current_time(),
+            clock: None,
+            comment: None,
+        };
+        let history = Arc::make_mut(&mut self.history);
+        if history.start.is_none() {
+            let mut start = before_move.clone();
+            start.clear_history();
+            history.start = Some(Box::new(start));
+            history.start_hash = before_move.position_hash();
+        }
+        history.entries.push(entry);
+    }
+
+    pub(crate) fn clear_history(&mut self) {
+        self.history = Default::default();
+    }
+}
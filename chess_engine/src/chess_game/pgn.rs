@@ -0,0 +1,108 @@
+use super::{Game, GameTree};
+
+impl Game {
+    // Get the game in Portable Game Notation, with the comments of the moves
+    pub fn to_pgn(&self) -> String {
+        let mut tree = GameTree::new(self.start_position());
+        for entry in self.history() {
+            // The moves were played from the same start, so they can always be played again
+            if tree.play_move(entry.chess_move).is_err() {
+                break;
+            }
+            tree.current_node_mut().comment = entry.comment.clone();
+        }
+        // The seven tags every PGN game should have, unknown values are written as question marks
+        return tree.to_pgn_with_tags(&[
+            ("Event", "?"),
+            ("Site", "?"),
+            ("Date", "????.??.??"),
+            ("Round", "?"),
+            ("White", "?"),
+            ("Black", "?"),
+        ]);
+    }
+
+    // Read the main line of a game in Portable Game Notation, variations are skipped
+    pub fn from_pgn(pgn: &str) -> Result<Game, String> {
+        let mut start_fen: Option<String> = None;
+        let mut movetext = String::new();
+        for line in pgn.lines() {
+            let line = line.trim();
+            if line.starts_with('[') && line.ends_with(']') {
+                let tag = &line[1..line.len() - 1];
+                if let Some((name, value)) = tag.split_once(' ') {
+                    if name == "FEN" {
+                        start_fen = Some(value.trim().trim_matches('"').to_string());
+                    }
+                }
+                continue;
+            }
+            movetext += line;
+            movetext.push('\n');
+        }
+
+        let mut game = match start_fen {
+            Some(fen) => Game::from_fen(&fen)?,
+            None => {
+                let mut game = Game::new();
+                game.set_up_board();
+                game
+            }
+        };
+
+        let mut chars = movetext.chars().peekable();
+        let mut token = String::new();
+        let mut variation_depth = 0;
+        loop {
+            let letter = chars.next();
+            let ends_token = match letter {
+                None => true,
+                Some(letter) => letter.is_whitespace() || "{};()".contains(letter),
+            };
+            if ends_token && !token.is_empty() {
+                if variation_depth == 0 {
+                    game.play_pgn_token(&token)?;
+                }
+                token.clear();
+            }
+            match letter {
+                None => break,
+                Some('{') => {
+                    let comment: String = chars.by_ref().take_while(|&letter| letter != '}').collect();
+                    if variation_depth == 0 && !game.history().is_empty() {
+                        game.set_last_move_comment(Some(comment.trim().to_string()))?;
+                    }
+                }
+                Some(';') => {
+                    // Comment to the end of the line
+                    chars.by_ref().take_while(|&letter| letter != '\n').for_each(drop);
+                }
+                Some('(') => variation_depth += 1,
+                Some(')') => {
+                    if variation_depth == 0 {
+                        return Err("Unexpected ) in PGN".to_string());
+                    }
+                    variation_depth -= 1;
+                }
+                Some(letter) if !letter.is_whitespace() => token.push(letter),
+                _ => {}
+            }
+        }
+        return Ok(game);
+    }
+
+    // Play a move from PGN movetext, skipping move numbers, annotation glyphs and results
+    fn play_pgn_token(&mut self, token: &str) -> Result<(), String> {
+        // Move numbers can be written together with the move, ex "12.e4" or "12...e5"
+        let text = match token.rfind('.') {
+            Some(index) => &token[index + 1..],
+            None => token,
+        };
+        if text.is_empty() || text.starts_with('$') || ["1-0", "0-1", "1/2-1/2", "*"].contains(&token) {
+            return Ok(());
+        }
+        let chess_move = self.parse_san(text)
+            .map_err(|error| format!("Move {} ({}): {}", self.fullmove_number(), text, error))?;
+        return self.move_piece(chess_move.board_move, true, chess_move.promote_piece);
+    }
+}
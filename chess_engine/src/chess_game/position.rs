@@ -0,0 +1,271 @@
+use std::fmt;
+
+use super::{BoardMove, BoardPosition, CastleSide, CastlingRights, ChessPieceColor, ChessPieceId, Game};
+
+// A reason why a position can not occur in a real game
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PositionError {
+    MissingKing(ChessPieceColor),
+    TooManyKings(ChessPieceColor),
+    TooManyPawns(ChessPieceColor),
+    TooManyPieces(ChessPieceColor),
+    PawnOnBackRank(BoardPosition),
+    OpponentInCheck, // The player who just moved can not still be in check
+    InvalidCastlingRights(ChessPieceColor), // The king or rook needed to castle is not in place
+    InvalidEnPassant(BoardPosition), // No pawn can just have moved past the square
+}
+
+impl fmt::Display for PositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PositionError::MissingKing(color) => return write!(f, "{:?} has no king", color),
+            PositionError::TooManyKings(color) => return write!(f, "{:?} has more than one king", color),
+            PositionError::TooManyPawns(color) => return write!(f, "{:?} has more than 8 pawns", color),
+            PositionError::TooManyPieces(color) => return write!(f, "{:?} has more than 16 pieces", color),
+            PositionError::PawnOnBackRank(pos) => {
+                return write!(f, "Pawn on {} can not be on the first or last rank", pos.to_algebraic_notation())
+            }
+            PositionError::OpponentInCheck => return write!(f, "The player who is not to move is in check"),
+            PositionError::InvalidCastlingRights(color) => {
+                return write!(f, "{:?} can not castle without king and rook in place", color)
+            }
+            PositionError::InvalidEnPassant(pos) => {
+                return write!(f, "No pawn can be taken en passant on {}", pos.to_algebraic_notation())
+            }
+        }
+    }
+}
+
+impl PositionError {
+    // Join the descriptions of several errors, used where errors are reported as text
+    pub fn join(errors: &[PositionError]) -> String {
+        let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+        return errors.join(", ");
+    }
+}
+
+impl Game {
+    // Make sure the position on the board could occur in a real game
+    pub fn validate(&self) -> Result<(), Vec<PositionError>> {
+        let mut errors: Vec<PositionError> = Vec::new();
+        for color in [ChessPieceColor::White, ChessPieceColor::Black] {
+            let mut kings = 0;
+            let mut pawns = 0;
+            let mut pieces = 0;
+            for x in 0..8 {
+                for y in 0..8 {
+                    let piece = self.piece_at(BoardPosition::new(x, y));
+                    if piece.is_none() || piece.unwrap().color != color {
+                        continue;
+                    }
+                    pieces += 1;
+                    match piece.unwrap().id {
+                        ChessPieceId::King => kings += 1,
+                        ChessPieceId::Pawn => {
+                            pawns += 1;
+                            if y == 0 || y == 7 {
+                                errors.push(PositionError::PawnOnBackRank(BoardPosition::new(x, y)));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            if kings == 0 {
+                errors.push(PositionError::MissingKing(color));
+            }
+            if kings > 1 {
+                errors.push(PositionError::TooManyKings(color));
+            }
+            if pawns > 8 {
+                errors.push(PositionError::TooManyPawns(color));
+            }
+            if pieces > 16 {
+                errors.push(PositionError::TooManyPieces(color));
+            }
+        }
+
+        // Make sure the player who is not to move is not in check
+        let mut board_copy = self.clone();
+        board_copy.turn = match self.turn {
+            ChessPieceColor::White => ChessPieceColor::Black,
+            ChessPieceColor::Black => ChessPieceColor::White,
+        };
+        if board_copy.is_check().is_some() {
+            errors.push(PositionError::OpponentInCheck);
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        return Ok(());
+    }
+}
+
+// Sets up a custom position, and makes sure it is valid before a game can be started from it
+#[derive(Clone)]
+pub struct PositionBuilder {
+    game: Game,
+    castling_rights: CastlingRights,
+    en_passant: Option<BoardPosition>,
+    halfmove_clock: u32,
+    fullmove_number: u32,
+}
+
+impl PositionBuilder {
+    // Start from an empty board with white to move and no castling rights
+    pub fn new() -> Self {
+        let mut game = Game::new();
+        game.empty_board();
+        return Self {
+            game,
+            castling_rights: CastlingRights::none(),
+            en_passant: None,
+            halfmove_clock: 0,
+            fullmove_number: 1,
+        };
+    }
+
+    pub fn piece(mut self, pos: BoardPosition, id: ChessPieceId, color: ChessPieceColor) -> Self {
+        if self.game.inside_board(pos).is_ok() {
+            self.game.set_pos(pos, id, color);
+        }
+        return self;
+    }
+
+    pub fn remove_piece(mut self, pos: BoardPosition) -> Self {
+        if self.game.inside_board(pos).is_ok() {
+            self.game.set_pos_to_none(pos);
+        }
+        return self;
+    }
+
+    pub fn turn(mut self, color: ChessPieceColor) -> Self {
+        self.game.turn = color;
+        return self;
+    }
+
+    pub fn castling_rights(mut self, color: ChessPieceColor, king_side: bool, queen_side: bool) -> Self {
+        self.castling_rights.set(color, CastleSide::KingSide, king_side);
+        self.castling_rights.set(color, CastleSide::QueenSide, queen_side);
+        return self;
+    }
+
+    // Set the square a pawn passed when it moved two squares on the last move
+    pub fn en_passant(mut self, pos: Option<BoardPosition>) -> Self {
+        self.en_passant = pos;
+        return self;
+    }
+
+    pub fn halfmove_clock(mut self, halfmove_clock: u32) -> Self {
+        self.halfmove_clock = halfmove_clock;
+        return self;
+    }
+
+    pub fn fullmove_number(mut self, fullmove_number: u32) -> Self {
+        self.fullmove_number = fullmove_number.max(1);
+        return self;
+    }
+
+    // Get the game, if the position is valid
+    pub fn build(&self) -> Result<Game, Vec<PositionError>> {
+        let (game, errors) = self.build_with_errors();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        return Ok(game);
+    }
+
+    // Get the game without making sure the position is valid, for positions written by the crate itself,
+    // which can be games built by hand. An en passant square that is not possible is left out
+    pub(crate) fn build_unchecked(&self) -> Game {
+        return self.build_with_errors().0;
+    }
+
+    fn build_with_errors(&self) -> (Game, Vec<PositionError>) {
+        let mut game = self.game.clone();
+        let mut errors: Vec<PositionError> = Vec::new();
+        game.move_count_left = 100 - self.halfmove_clock.min(100) as i64;
+        game.fullmove_number = self.fullmove_number;
+        game.castling_rights = self.castling_rights;
+
+        // Pawns that are not on their start rank have moved
+        for x in 0..8 {
+            for y in 0..8 {
+                let piece = game.get_board_ref(BoardPosition::new(x, y)).unwrap().as_mut();
+                if piece.is_none() {
+                    continue;
+                }
+                let piece = piece.unwrap();
+                if piece.id == ChessPieceId::Pawn {
+                    let start_y = if piece.color == ChessPieceColor::White { 6 } else { 1 };
+                    piece.moved = y != start_y;
+                }
+            }
+        }
+
+        // Castling needs the king on its first rank and the rook in the corner
+        for color in [ChessPieceColor::White, ChessPieceColor::Black] {
+            let king_side = self.castling_rights.has(color, CastleSide::KingSide);
+            let queen_side = self.castling_rights.has(color, CastleSide::QueenSide);
+            if !king_side && !queen_side {
+                continue;
+            }
+            let back_y = if color == ChessPieceColor::White { 7 } else { 0 };
+            let mut in_place = Self::has_piece(&game, ChessPieceId::King, color, 1..7, back_y);
+            if king_side {
+                in_place = in_place && Self::has_piece(&game, ChessPieceId::Rook, color, 7..8, back_y);
+            }
+            if queen_side {
+                in_place = in_place && Self::has_piece(&game, ChessPieceId::Rook, color, 0..1, back_y);
+            }
+            if !in_place {
+                errors.push(PositionError::InvalidCastlingRights(color));
+            }
+        }
+
+        // The pawn that can be taken en passant has just moved two squares
+        if self.en_passant.is_some() {
+            let pos = self.en_passant.unwrap();
+            // White pawns pass the third rank, black pawns pass the sixth rank
+            let (passed_y, pawn_color) = match game.turn {
+                ChessPieceColor::Black => (5, ChessPieceColor::White),
+                ChessPieceColor::White => (2, ChessPieceColor::Black),
+            };
+            let from_y = if pawn_color == ChessPieceColor::White { 6 } else { 1 };
+            let to_y = if pawn_color == ChessPieceColor::White { 4 } else { 3 };
+            let pawn = game.piece_at(BoardPosition::new(pos.x, to_y));
+            if pos.x > 7 || pos.y != passed_y
+                || pawn.is_none() || pawn.unwrap().id != ChessPieceId::Pawn || pawn.unwrap().color != pawn_color
+                || game.piece_at(pos).is_some() || game.piece_at(BoardPosition::new(pos.x, from_y)).is_some() {
+                errors.push(PositionError::InvalidEnPassant(pos));
+            }
+            else {
+                game.last_move = Some(BoardMove::new(pos.x, from_y, pos.x, to_y));
+                game.last_move_passant = true;
+            }
+        }
+
+        if let Err(position_errors) = game.validate() {
+            errors.extend(position_errors);
+        }
+        return (game, errors);
+    }
+
+    // See if there is a piece on any of the given files of a rank
+    fn has_piece(game: &Game, id: ChessPieceId, color: ChessPieceColor, files: std::ops::Range<u8>, y: u8) -> bool {
+        for x in files {
+            let piece = game.piece_at(BoardPosition::new(x, y));
+            if piece.is_some() && piece.unwrap().id == id && piece.unwrap().color == color {
+                return true;
+            }
+        }
+        return false;
+    }
+}
+
+impl Default for PositionBuilder {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+use super::ChessPieceColor;
+
+// A request from a client of the play server
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    // Start a game, None to get a random color
+    Create { color: Option<ChessPieceColor>, time_control: Option<String> },
+    Join { code: String },
+    Watch { code: String },
+    // Take a seat back after losing the connection, with the token given when joining
+    Resume { code: String, token: String },
+    // A move in UCI or algebraic notation
+    Move { text: String },
+    Resign,
+    State,
+}
+
+// A reply or an event sent by the play server
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Created { code: String, color: ChessPieceColor, token: String },
+    Joined { code: String, color: ChessPieceColor, token: String },
+    Watching { code: String },
+    Resumed { code: String, color: ChessPieceColor },
+    // Sent to everyone in the game when it starts, and to anyone asking for the state
+    State { fen: String, white_ms: Option<u64>, black_ms: Option<u64>, moves: Vec<String> },
+    Moved { uci: String, san: String, fen: String, white_ms: Option<u64>, black_ms: Option<u64> },
+    OpponentLeft,
+    OpponentReturned,
+    GameOver { result: String, reason: String },
+    Error { message: String },
+}
+
+fn get_color_name(color: ChessPieceColor) -> &'static str {
+    match color {
+        ChessPieceColor::White => return "white",
+        ChessPieceColor::Black => return "black",
+    }
+}
+
+fn parse_color(text: &str) -> Result<ChessPieceColor, String> {
+    match text {
+        "white" => return Ok(ChessPieceColor::White),
+        "black" => return Ok(ChessPieceColor::Black),
+        _ => return Err(format!("Invalid color '{}'", text)),
+    }
+}
+
+// Clock times are written in milliseconds, or '-' when the game has no clock
+fn get_time_text(time: Option<u64>) -> String {
+    return time.map(|time| time.to_string()).unwrap_or_else(|| "-".to_string());
+}
+
+fn parse_time(text: &str) -> Result<Option<u64>, String> {
+    if text == "-" {
+        return Ok(None);
+    }
+    return text.parse().map(Some).map_err(|_| format!("Invalid time '{}'", text));
+}
+
+fn get_word<'a>(words: &[&'a str], index: usize) -> Result<&'a str, String> {
+    return words.get(index).copied().ok_or_else(|| format!("Missing argument {} for {}", index, words[0]));
+}
+
+// The messages are also written as single lines of words for the plain TCP protocol, ex "join ABCD".
+// FEN has spaces, so it is always the last field of a line
+impl ClientMessage {
+    pub fn to_line(&self) -> String {
+        match self {
+            ClientMessage::Create { color, time_control } => {
+                let color = color.map(get_color_name).unwrap_or("random");
+                return match time_control {
+                    Some(time_control) => format!("create {} {}", color, time_control),
+                    None => format!("create {}", color),
+                };
+            }
+            ClientMessage::Join { code } => return format!("join {}", code),
+            ClientMessage::Watch { code } => return format!("watch {}", code),
+            ClientMessage::Resume { code, token } => return format!("resume {} {}", code, token),
+            ClientMessage::Move { text } => return format!("move {}", text),
+            ClientMessage::Resign => return "resign".to_string(),
+            ClientMessage::State => return "state".to_string(),
+        }
+    }
+
+    pub fn from_line(line: &str) -> Result<ClientMessage, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            return Err("Empty message".to_string());
+        }
+        match words[0] {
+            "create" => {
+                let color = match words.get(1).copied() {
+                    None | Some("random") => None,
+                    Some(text) => Some(parse_color(text)?),
+                };
+                return Ok(ClientMessage::Create { color, time_control: words.get(2).map(|text| text.to_string()) });
+            }
+            "join" => return Ok(ClientMessage::Join { code: get_word(&words, 1)?.to_string() }),
+            "watch" => return Ok(ClientMessage::Watch { code: get_word(&words, 1)?.to_string() }),
+            "resume" => return Ok(ClientMessage::Resume {
+                code: get_word(&words, 1)?.to_string(),
+                token: get_word(&words, 2)?.to_string(),
+            }),
+            "move" => return Ok(ClientMessage::Move { text: get_word(&words, 1)?.to_string() }),
+            "resign" => return Ok(ClientMessage::Resign),
+            "state" => return Ok(ClientMessage::State),
+            _ => return Err(format!("Unknown message '{}'", words[0])),
+        }
+    }
+}
+
+impl ServerMessage {
+    pub fn to_line(&self) -> String {
+        match self {
+            ServerMessage::Created { code, color, token } => return format!("created {} {} {}", code, get_color_name(*color), token),
+            ServerMessage::Joined { code, color, token } => return format!("joined {} {} {}", code, get_color_name(*color), token),
+            ServerMessage::Watching { code } => return format!("watching {}", code),
+            ServerMessage::Resumed { code, color } => return format!("resumed {} {}", code, get_color_name(*color)),
+            ServerMessage::State { fen, white_ms, black_ms, moves } => {
+                let moves = if moves.is_empty() { "-".to_string() } else { moves.join(",") };
+                return format!("state {} {} {} {}", get_time_text(*white_ms), get_time_text(*black_ms), moves, fen);
+            }
+            ServerMessage::Moved { uci, san, fen, white_ms, black_ms } => {
+                return format!("moved {} {} {} {} {}", uci, san, get_time_text(*white_ms), get_time_text(*black_ms), fen);
+            }
+            ServerMessage::OpponentLeft => return "opponent_left".to_string(),
+            ServerMessage::OpponentReturned => return "opponent_returned".to_string(),
+            ServerMessage::GameOver { result, reason } => return format!("game_over {} {}", result, reason),
+            ServerMessage::Error { message } => return format!("error {}", message),
+        }
+    }
+
+    pub fn from_line(line: &str) -> Result<ServerMessage, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        if words.is_empty() {
+            return Err("Empty message".to_string());
+        }
+        // Everything from a word to the end of the line
+        let get_rest = |index: usize| -> Result<String, String> {
+            get_word(&words, index)?;
+            return Ok(words[index..].join(" "));
+        };
+        match words[0] {
+            "created" | "joined" => {
+                let code = get_word(&words, 1)?.to_string();
+                let color = parse_color(get_word(&words, 2)?)?;
+                let token = get_word(&words, 3)?.to_string();
+                if words[0] == "created" {
+                    return Ok(ServerMessage::Created { code, color, token });
+                }
+                return Ok(ServerMessage::Joined { code, color, token });
+            }
+            "watching" => return Ok(ServerMessage::Watching { code: get_word(&words, 1)?.to_string() }),
+            "resumed" => return Ok(ServerMessage::Resumed {
+                code: get_word(&words, 1)?.to_string(),
+                color: parse_color(get_word(&words, 2)?)?,
+            }),
+            "state" => {
+                let moves = match get_word(&words, 3)? {
+                    "-" => Vec::new(),
+                    text => text.split(',').map(|text| text.to_string()).collect(),
+                };
+                return Ok(ServerMessage::State {
+                    fen: get_rest(4)?,
+                    white_ms: parse_time(get_word(&words, 1)?)?,
+                    black_ms: parse_time(get_word(&words, 2)?)?,
+                    moves,
+                });
+            }
+            "moved" => return Ok(ServerMessage::Moved {
+                uci: get_word(&words, 1)?.to_string(),
+                san: get_word(&words, 2)?.to_string(),
+                white_ms: parse_time(get_word(&words, 3)?)?,
+                black_ms: parse_time(get_word(&words, 4)?)?,
+                fen: get_rest(5)?,
+            }),
+            "opponent_left" => return Ok(ServerMessage::OpponentLeft),
+            "opponent_returned" => return Ok(ServerMessage::OpponentReturned),
+            "game_over" => return Ok(ServerMessage::GameOver {
+                result: get_word(&words, 1)?.to_string(),
+                reason: get_rest(2)?,
+            }),
+            "error" => return Ok(ServerMessage::Error { message: get_rest(1).unwrap_or_default() }),
+            _ => return Err(format!("Unknown message '{}'", words[0])),
+        }
+    }
+}
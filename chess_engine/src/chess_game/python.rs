@@ -0,0 +1,213 @@
+use pyo3::{exceptions::PyValueError, prelude::*, types::PyType};
+
+use super::{ChessPieceColor, Game, Move};
+
+fn get_py_error(error: String) -> PyErr {
+    return PyValueError::new_err(error);
+}
+
+// A move for Python, with the piece a pawn is promoted to. Compared and shown in UCI notation
+#[pyclass(name = "BoardMove", module = "chess_engine", frozen)]
+#[derive(Clone, Copy)]
+pub struct PyBoardMove {
+    pub chess_move: Move,
+}
+
+#[pymethods]
+impl PyBoardMove {
+    // Parse a move in UCI notation, ex "e2e4" or "e7e8q"
+    #[new]
+    pub fn new(uci: &str) -> PyResult<PyBoardMove> {
+        return Ok(PyBoardMove { chess_move: Move::from_uci(uci).map_err(get_py_error)? });
+    }
+
+    #[classmethod]
+    pub fn from_uci(_class: &Bound<'_, PyType>, uci: &str) -> PyResult<PyBoardMove> {
+        return PyBoardMove::new(uci);
+    }
+
+    pub fn uci(&self) -> String {
+        return self.chess_move.to_uci();
+    }
+
+    // The square the piece moves from, ex "e2"
+    #[getter]
+    pub fn from_square(&self) -> String {
+        return self.chess_move.board_move.from_pos.to_algebraic_notation();
+    }
+
+    #[getter]
+    pub fn to_square(&self) -> String {
+        return self.chess_move.board_move.to_pos.to_algebraic_notation();
+    }
+
+    // The letter of the piece promoted to, ex "q", or None
+    #[getter]
+    pub fn promotion(&self) -> Option<String> {
+        return self.chess_move.promote_piece.map(|piece| piece.to_uci_letter().to_string());
+    }
+
+    pub fn __str__(&self) -> String {
+        return self.uci();
+    }
+
+    pub fn __repr__(&self) -> String {
+        return format!("BoardMove('{}')", self.uci());
+    }
+
+    pub fn __eq__(&self, other: &Bound<'_, PyAny>) -> bool {
+        match other.extract::<PyBoardMove>() {
+            Ok(other) => return self.uci() == other.uci(),
+            Err(_) => return false,
+        }
+    }
+
+    pub fn __hash__(&self) -> u64 {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        self.uci().hash(&mut hasher);
+        return hasher.finish();
+    }
+}
+
+// A game for Python, methods are named as in the python-chess Board. Rule errors raise ValueError
+#[pyclass(name = "Game", module = "chess_engine")]
+#[derive(Clone)]
+pub struct PyGame {
+    pub game: Game,
+}
+
+#[pymethods]
+impl PyGame {
+    // Start from a FEN, or the standard position
+    #[new]
+    #[pyo3(signature = (fen = None))]
+    pub fn new(fen: Option<&str>) -> PyResult<PyGame> {
+        match fen {
+            Some(fen) => return Ok(PyGame { game: Game::from_fen(fen).map_err(get_py_error)? }),
+            None => {
+                let mut game = Game::new();
+                game.set_up_board();
+                return Ok(PyGame { game });
+            }
+        }
+    }
+
+    #[classmethod]
+    pub fn from_pgn(_class: &Bound<'_, PyType>, pgn: &str) -> PyResult<PyGame> {
+        return Ok(PyGame { game: Game::from_pgn(pgn).map_err(get_py_error)? });
+    }
+
+    pub fn fen(&self) -> String {
+        return self.game.to_fen();
+    }
+
+    pub fn pgn(&self) -> String {
+        return self.game.to_pgn();
+    }
+
+    // The legal moves of the player to move, for iterating over or counting
+    #[getter]
+    pub fn legal_moves(&self) -> Vec<PyBoardMove> {
+        return self.game.legal_moves().into_iter().map(|chess_move| PyBoardMove { chess_move }).collect();
+    }
+
+    pub fn is_legal(&self, board_move: &PyBoardMove) -> bool {
+        let uci = board_move.uci();
+        return self.game.legal_moves().iter().any(|chess_move| chess_move.to_uci() == uci);
+    }
+
+    pub fn push(&mut self, board_move: &PyBoardMove) -> PyResult<()> {
+        let chess_move = board_move.chess_move;
+        return self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece).map_err(get_py_error);
+    }
+
+    pub fn push_uci(&mut self, uci: &str) -> PyResult<PyBoardMove> {
+        let board_move = PyBoardMove::new(uci)?;
+        self.push(&board_move)?;
+        return Ok(board_move);
+    }
+
+    pub fn push_san(&mut self, san: &str) -> PyResult<PyBoardMove> {
+        let board_move = self.parse_san(san)?;
+        self.push(&board_move)?;
+        return Ok(board_move);
+    }
+
+    // Take back the last move and return it
+    pub fn pop(&mut self) -> PyResult<PyBoardMove> {
+        let entry = self.game.undo_move().map_err(get_py_error)?;
+        return Ok(PyBoardMove { chess_move: entry.chess_move });
+    }
+
+    pub fn parse_san(&self, san: &str) -> PyResult<PyBoardMove> {
+        return Ok(PyBoardMove { chess_move: self.game.parse_san(san).map_err(get_py_error)? });
+    }
+
+    pub fn san(&self, board_move: &PyBoardMove) -> String {
+        return self.game.move_to_san(&board_move.chess_move);
+    }
+
+    // The moves played, oldest first
+    #[getter]
+    pub fn move_stack(&self) -> Vec<PyBoardMove> {
+        return self.game.history().iter().map(|entry| PyBoardMove { chess_move: entry.chess_move }).collect();
+    }
+
+    // The moves played in algebraic notation
+    pub fn san_history(&self) -> Vec<String> {
+        return self.game.history().iter().map(|entry| entry.san.clone()).collect();
+    }
+
+    // "white" or "black"
+    #[getter]
+    pub fn turn(&self) -> &'static str {
+        match self.game.turn {
+            ChessPieceColor::White => return "white",
+            ChessPieceColor::Black => return "black",
+        }
+    }
+
+    pub fn is_check(&self) -> bool {
+        return self.game.is_check().is_some();
+    }
+
+    pub fn is_checkmate(&self) -> bool {
+        return self.game.is_check_mate();
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        return self.game.is_stale_mate();
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        return self.game.game_is_over();
+    }
+
+    // The result as in PGN, "1-0", "0-1", "1/2-1/2", or "*" while the game goes on
+    pub fn result(&self) -> &'static str {
+        match self.game.get_winner() {
+            Some(ChessPieceColor::White) => return "1-0",
+            Some(ChessPieceColor::Black) => return "0-1",
+            None if self.game.game_is_over() => return "1/2-1/2",
+            None => return "*",
+        }
+    }
+
+    pub fn copy(&self) -> PyGame {
+        return self.clone();
+    }
+
+    pub fn __repr__(&self) -> String {
+        return format!("Game('{}')", self.fen());
+    }
+}
+
+// The chess_engine extension module
+#[pymodule]
+#[pyo3(name = "chess_engine")]
+pub fn python_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<PyGame>()?;
+    module.add_class::<PyBoardMove>()?;
+    return Ok(());
+}
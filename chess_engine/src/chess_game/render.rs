@@ -0,0 +1,262 @@
+use std::{fmt, str::FromStr};
+
+use super::{write_color, BoardMove, BoardPosition, ChessPiece, ChessPieceColor, ColorTerminal, Game, Move};
+
+// What to show when rendering a board
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub orientation: ChessPieceColor, // The side shown at the bottom of the board
+    pub coordinates: bool,
+    pub highlights: Vec<BoardPosition>, // Ex the squares a piece can move to
+    pub selected: Option<BoardPosition>,
+    pub mark_last_move: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        return Self {
+            orientation: ChessPieceColor::White,
+            coordinates: true,
+            highlights: Vec::new(),
+            selected: None,
+            mark_last_move: false,
+        };
+    }
+}
+
+// How a square should be marked when it is rendered
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SquareMark {
+    None,
+    Highlight,
+    Selected,
+    LastMove,
+}
+
+impl RenderOptions {
+    // Get the board positions in the order they are drawn, one vector for each row from the top
+    pub fn get_rows(&self) -> Vec<Vec<BoardPosition>> {
+        let mut rows = Vec::new();
+        for row in 0..8 {
+            let mut positions = Vec::new();
+            for column in 0..8 {
+                match self.orientation {
+                    ChessPieceColor::White => positions.push(BoardPosition::new(column, row)),
+                    ChessPieceColor::Black => positions.push(BoardPosition::new(7 - column, 7 - row)),
+                }
+            }
+            rows.push(positions);
+        }
+        return rows;
+    }
+
+    // Get the file letters in the order they are drawn, ex "a b c d e f g h"
+    pub fn get_file_labels(&self) -> String {
+        let letters: Vec<String> = self.get_rows()[0].iter()
+            .map(|pos| BoardPosition::get_letter_from_coordinate(pos.x).unwrap().to_string())
+            .collect();
+        return letters.join(" ");
+    }
+
+    // Get how a square is marked, the selected square is shown before highlights and highlights before the last move
+    pub fn get_mark(&self, game: &Game, pos: BoardPosition) -> SquareMark {
+        if self.selected == Some(pos) {
+            return SquareMark::Selected;
+        }
+        if self.highlights.contains(&pos) {
+            return SquareMark::Highlight;
+        }
+        if self.mark_last_move && game.last_move().is_some() {
+            let last_move = game.last_move().unwrap();
+            if last_move.from_pos == pos || last_move.to_pos == pos {
+                return SquareMark::LastMove;
+            }
+        }
+        return SquareMark::None;
+    }
+}
+
+// Draws a board as text, without writing it anywhere itself
+pub trait BoardRenderer {
+    fn render(&self, game: &Game, options: &RenderOptions, out: &mut dyn fmt::Write) -> fmt::Result;
+
+    fn render_to_string(&self, game: &Game, options: &RenderOptions) -> String {
+        let mut text = String::new();
+        self.render(game, options, &mut text).unwrap();
+        return text;
+    }
+}
+
+// Draw the rows of a board as text, each square is a piece glyph followed by a character for its mark
+fn render_text(
+    game: &Game,
+    options: &RenderOptions,
+    out: &mut dyn fmt::Write,
+    get_glyph: fn(Option<ChessPiece>) -> char,
+) -> fmt::Result {
+    if options.coordinates {
+        writeln!(out, "  {}", options.get_file_labels())?;
+    }
+    for row in options.get_rows() {
+        let rank = BoardPosition::get_number_from_coordinate(row[0].y).unwrap();
+        if options.coordinates {
+            write!(out, "{} ", rank)?;
+        }
+        for pos in row {
+            let mark = match options.get_mark(game, pos) {
+                SquareMark::None => ' ',
+                SquareMark::Highlight => '*',
+                SquareMark::Selected => '+',
+                SquareMark::LastMove => '~',
+            };
+            write!(out, "{}{}", get_glyph(game.piece_at(pos)), mark)?;
+        }
+        if options.coordinates {
+            write!(out, "{}", rank)?;
+        }
+        writeln!(out)?;
+    }
+    if options.coordinates {
+        writeln!(out, "  {}", options.get_file_labels())?;
+    }
+    return Ok(());
+}
+
+// Letters as in FEN, upper case for white and '.' for empty squares.
+// Marked squares are followed by '*' for highlights, '+' for the selected square and '~' for the last move
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AsciiRenderer;
+
+impl BoardRenderer for AsciiRenderer {
+    fn render(&self, game: &Game, options: &RenderOptions, out: &mut dyn fmt::Write) -> fmt::Result {
+        return render_text(game, options, out, |piece| match piece {
+            Some(piece) if piece.color == ChessPieceColor::White => piece.id.to_uci_letter().to_ascii_uppercase(),
+            Some(piece) => piece.id.to_uci_letter(),
+            None => '.',
+        });
+    }
+}
+
+// Unicode chess glyphs and '·' for empty squares, marked like AsciiRenderer
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UnicodeRenderer;
+
+impl BoardRenderer for UnicodeRenderer {
+    fn render(&self, game: &Game, options: &RenderOptions, out: &mut dyn fmt::Write) -> fmt::Result {
+        return render_text(game, options, out, |piece| match piece {
+            Some(piece) => piece.unicode_char,
+            None => '·',
+        });
+    }
+}
+
+// Unicode chess glyphs on squares colored with ANSI escape codes, for terminals
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AnsiRenderer;
+
+impl BoardRenderer for AnsiRenderer {
+    fn render(&self, game: &Game, options: &RenderOptions, out: &mut dyn fmt::Write) -> fmt::Result {
+        if options.coordinates {
+            writeln!(out, "  {}", options.get_file_labels())?;
+        }
+        for row in options.get_rows() {
+            let rank = BoardPosition::get_number_from_coordinate(row[0].y).unwrap();
+            if options.coordinates {
+                write!(out, "{} ", rank)?;
+            }
+            for pos in row {
+                let background_color = match options.get_mark(game, pos) {
+                    SquareMark::Highlight => ColorTerminal::Red,
+                    SquareMark::Selected => ColorTerminal::Green,
+                    SquareMark::LastMove => ColorTerminal::Yellow,
+                    SquareMark::None if (pos.x + pos.y) % 2 == 0 => ColorTerminal::LightBlue,
+                    SquareMark::None => ColorTerminal::Blue,
+                };
+                match game.piece_at(pos) {
+                    // If there is nothing there, just write two spaces
+                    None => write_color(out, "  ", ColorTerminal::White, background_color)?,
+                    Some(piece) => {
+                        let text_color = match piece.color {
+                            ChessPieceColor::White => ColorTerminal::White,
+                            ChessPieceColor::Black => ColorTerminal::Black,
+                        };
+                        write_color(out, piece.unicode_char.to_string().as_str(), text_color, background_color)?;
+                        write_color(out, " ", text_color, background_color)?;
+                    }
+                }
+            }
+            if options.coordinates {
+                write!(out, "{} ", rank)?;
+            }
+            writeln!(out)?;
+        }
+        if options.coordinates {
+            writeln!(out, "  {}", options.get_file_labels())?;
+        }
+        return Ok(());
+    }
+}
+
+// Written in algebraic notation, ex "e4"
+impl fmt::Display for BoardPosition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.to_algebraic_notation());
+    }
+}
+
+impl FromStr for BoardPosition {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<BoardPosition, String> {
+        return BoardPosition::from_algebraic_notation(text);
+    }
+}
+
+// Written in UCI notation, ex "e2e4"
+impl fmt::Display for BoardMove {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.to_uci());
+    }
+}
+
+impl FromStr for BoardMove {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<BoardMove, String> {
+        return BoardMove::from_uci(text);
+    }
+}
+
+// Written in UCI notation, ex "e7e8q"
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.to_uci());
+    }
+}
+
+impl FromStr for Move {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Move, String> {
+        return Move::from_uci(text);
+    }
+}
+
+// Written as FEN, or as an ASCII board with the alternate flag "{:#}"
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return AsciiRenderer.render(self, &RenderOptions::default(), f);
+        }
+        return write!(f, "{}", self.to_fen());
+    }
+}
+
+// Read from FEN
+impl FromStr for Game {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Game, String> {
+        return Game::from_fen(text);
+    }
+}
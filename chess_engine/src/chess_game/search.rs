@@ -0,0 +1,103 @@
+use super::{BoardPosition, ChessPieceColor, ChessPieceId, Game, Move};
+
+// Score of a checkmate, a quicker mate scores higher
+pub const MATE_SCORE: i32 = 100000;
+
+// Default number of half moves the built-in move picker looks ahead
+pub const DEFAULT_SEARCH_DEPTH: u32 = 2;
+
+fn get_piece_value(id: ChessPieceId) -> i32 {
+    match id {
+        ChessPieceId::Pawn => return 100,
+        ChessPieceId::Knight => return 300,
+        ChessPieceId::Bishop => return 310,
+        ChessPieceId::Rook => return 500,
+        ChessPieceId::Queen => return 900,
+        ChessPieceId::King => return 0,
+    }
+}
+
+impl Game {
+    // Get a score of the position in centipawns, positive if the player to move is better.
+    // Counts material, with a small bonus for pieces near the center and pawns that have advanced
+    pub fn evaluate(&self) -> i32 {
+        let mut score = 0;
+        for x in 0..8 {
+            for y in 0..8 {
+                let piece = self.piece_at(BoardPosition::new(x, y));
+                if piece.is_none() {
+                    continue;
+                }
+                let piece = piece.unwrap();
+                let mut value = get_piece_value(piece.id);
+                let center_distance = (2 * x as i32 - 7).abs() + (2 * y as i32 - 7).abs();
+                if piece.id == ChessPieceId::Knight || piece.id == ChessPieceId::Bishop {
+                    value += 14 - center_distance;
+                }
+                if piece.id == ChessPieceId::Pawn {
+                    let advanced = if piece.color == ChessPieceColor::White { 6 - y as i32 } else { y as i32 - 1 };
+                    value += 2 * advanced;
+                }
+                if piece.color == self.turn {
+                    score += value;
+                }
+                else {
+                    score -= value;
+                }
+            }
+        }
+        return score;
+    }
+
+    // Pick the best move by looking a number of half moves ahead, None if there are no legal moves
+    pub fn best_move(&self, depth: u32) -> Option<Move> {
+        return self.search(depth).map(|(chess_move, _)| chess_move);
+    }
+
+    // Get the best move and its score for the player to move
+    pub fn search(&self, depth: u32) -> Option<(Move, i32)> {
+        let mut best: Option<(Move, i32)> = None;
+        let mut alpha = -MATE_SCORE - 1;
+        for chess_move in self.legal_moves() {
+            let mut game = self.clone();
+            game.try_move_piece(chess_move.board_move, true, chess_move.promote_piece).unwrap();
+            let score = -game.negamax(depth.max(1) - 1, -MATE_SCORE - 1, -alpha);
+            if best.is_none() || score > best.unwrap().1 {
+                best = Some((chess_move, score));
+                alpha = alpha.max(score);
+            }
+        }
+        return best;
+    }
+
+    // Get the score of the position for the player to move, searching with alpha-beta pruning
+    fn negamax(&self, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+        if depth == 0 {
+            if self.is_check_mate() {
+                return -MATE_SCORE + 100;
+            }
+            return self.evaluate();
+        }
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            if self.is_check().is_some() {
+                // Mates found with more depth left are closer
+                return -MATE_SCORE + 100 - depth as i32;
+            }
+            return 0;
+        }
+        if self.max_move_count_reached() {
+            return 0;
+        }
+        for chess_move in moves {
+            let mut game = self.clone();
+            game.try_move_piece(chess_move.board_move, true, chess_move.promote_piece).unwrap();
+            let score = -game.negamax(depth - 1, -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        return alpha;
+    }
+}
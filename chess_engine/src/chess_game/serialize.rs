@@ -0,0 +1,75 @@
+use std::time::{Duration, SystemTime};
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{ChessPieceColor, Game, HistoryEntry, Move, NotationLocale};
+
+// A played move as it is stored, the rest of the history entry is recreated when the move is played again
+#[derive(Serialize, Deserialize)]
+struct SerializedMove {
+    uci: String,
+    san: String,
+    timestamp: SystemTime,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    clock: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+// A game is stored as the position it started from and the moves played, instead of the board itself
+#[derive(Serialize, Deserialize)]
+struct SerializedGame {
+    start_fen: String,
+    fen: String,
+    history: Vec<SerializedMove>,
+    notation_locale: NotationLocale,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    flag_fall: Option<ChessPieceColor>,
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let history = self.history().iter().map(|entry: &HistoryEntry| {
+            return SerializedMove {
+                uci: entry.chess_move.to_uci(),
+                san: entry.san.clone(),
+                timestamp: entry.timestamp,
+                clock: entry.clock,
+                comment: entry.comment.clone(),
+            };
+        });
+        let game = SerializedGame {
+            start_fen: self.start_fen(),
+            fen: self.to_fen(),
+            history: history.collect(),
+            notation_locale: self.notation_locale,
+            flag_fall: self.flag_fall(),
+        };
+        return game.serialize(serializer);
+    }
+}
+
+impl<'de> Deserialize<'de> for Game {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Game, D::Error> {
+        let serialized = SerializedGame::deserialize(deserializer)?;
+        // The start position was written by Game, which can be a position built by hand that from_fen would reject
+        let mut game = Game::from_fen_unchecked(&serialized.start_fen).map_err(D::Error::custom)?;
+        for serialized_move in &serialized.history {
+            let chess_move = Move::from_uci(&serialized_move.uci).map_err(D::Error::custom)?;
+            game.move_piece(chess_move.board_move, true, chess_move.promote_piece)
+                .map_err(|error| D::Error::custom(format!("{}: {}", serialized_move.uci, error)))?;
+            game.set_last_move_comment(serialized_move.comment.clone()).unwrap();
+            game.set_last_move_clock(serialized_move.clock).unwrap();
+            game.set_last_move_timestamp(serialized_move.timestamp);
+        }
+        // The board is replayed from the moves, so the stored position only has to agree with it
+        if game.to_fen() != serialized.fen {
+            return Err(D::Error::custom("The moves do not lead to the stored position"));
+        }
+        game.notation_locale = serialized.notation_locale;
+        if serialized.flag_fall.is_some() {
+            game.set_flag_fall(serialized.flag_fall.unwrap());
+        }
+        return Ok(game);
+    }
+}
@@ -0,0 +1,493 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tungstenite::Message;
+
+use super::{ChessPieceColor, ClientMessage, Clock, Game, ServerMessage, TimeControl};
+
+// Letters used in game codes, without I and O that are easily mistaken for numbers
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LENGTH: usize = 4;
+
+// How often running clocks are checked for flag fall
+const CLOCK_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+// How long an unfinished game is kept after everyone has left, so the players can still resume it
+const ABANDONED_GAME_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+// How long a WebSocket connection waits for a message before sending what is queued for it
+const WEBSOCKET_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+fn get_index(color: ChessPieceColor) -> usize {
+    match color {
+        ChessPieceColor::White => return 0,
+        ChessPieceColor::Black => return 1,
+    }
+}
+
+fn get_opponent(color: ChessPieceColor) -> ChessPieceColor {
+    match color {
+        ChessPieceColor::White => return ChessPieceColor::Black,
+        ChessPieceColor::Black => return ChessPieceColor::White,
+    }
+}
+
+fn get_error(message: &str) -> ServerMessage {
+    return ServerMessage::Error { message: message.to_string() };
+}
+
+// A player's place in a game, the token lets the player take it back after losing the connection
+struct Seat {
+    token: String,
+    connection: Option<(u64, Sender<ServerMessage>)>,
+}
+
+struct ServerGame {
+    game: Game,
+    clock: Option<Clock>,
+    seats: [Option<Seat>; 2], // White, then black
+    spectators: Vec<(u64, Sender<ServerMessage>)>,
+    result: Option<(String, String)>, // Result as in PGN and the reason
+    unused_since: Option<Instant>, // When the last player or spectator left
+}
+
+impl ServerGame {
+    fn is_started(&self) -> bool {
+        return self.seats.iter().all(|seat| seat.is_some());
+    }
+
+    // Returns true if no player or spectator is connected
+    fn is_unused(&self) -> bool {
+        let seats_unused = self.seats.iter().flatten().all(|seat| seat.connection.is_none());
+        return seats_unused && self.spectators.is_empty();
+    }
+
+    fn get_times(&self) -> (Option<u64>, Option<u64>) {
+        match self.clock.as_ref() {
+            Some(clock) => return (
+                Some(clock.remaining(ChessPieceColor::White).as_millis() as u64),
+                Some(clock.remaining(ChessPieceColor::Black).as_millis() as u64),
+            ),
+            None => return (None, None),
+        }
+    }
+
+    fn get_state(&self) -> ServerMessage {
+        let (white_ms, black_ms) = self.get_times();
+        return ServerMessage::State {
+            fen: self.game.to_fen(),
+            white_ms,
+            black_ms,
+            moves: self.game.history().iter().map(|entry| entry.chess_move.to_uci()).collect(),
+        };
+    }
+
+    // Send a message to the players and spectators that are connected
+    fn broadcast(&self, message: &ServerMessage) {
+        let seats = self.seats.iter().flatten().filter_map(|seat| seat.connection.as_ref());
+        for (_, sender) in seats.chain(self.spectators.iter()) {
+            let _ = sender.send(message.clone());
+        }
+    }
+
+    fn send_to(&self, color: ChessPieceColor, message: ServerMessage) {
+        let seat = self.seats[get_index(color)].as_ref();
+        if let Some((_, sender)) = seat.and_then(|seat| seat.connection.as_ref()) {
+            let _ = sender.send(message);
+        }
+    }
+
+    fn end(&mut self, result: &str, reason: &str) {
+        if let Some(clock) = self.clock.as_mut() {
+            clock.stop();
+        }
+        self.result = Some((result.to_string(), reason.to_string()));
+        self.broadcast(&ServerMessage::GameOver { result: result.to_string(), reason: reason.to_string() });
+    }
+
+    // End the game if it is over on the board or on the clock
+    fn check_game_over(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+        if let Some(clock) = self.clock.as_ref() {
+            self.game.update_from_clock(clock);
+        }
+        if !self.game.game_is_over() {
+            return;
+        }
+        let result = match self.game.get_winner() {
+            Some(ChessPieceColor::White) => "1-0",
+            Some(ChessPieceColor::Black) => "0-1",
+            None => "1/2-1/2",
+        };
+        let reason = if self.game.flag_fall().is_some() {
+            "time"
+        }
+        else if self.game.is_check_mate() {
+            "checkmate"
+        }
+        else if self.game.is_stale_mate() {
+            "stalemate"
+        }
+        else {
+            "fifty_moves"
+        };
+        self.end(result, reason);
+    }
+
+    fn play(&mut self, color: ChessPieceColor, text: &str) -> Result<(), String> {
+        if !self.is_started() {
+            return Err("Waiting for an opponent".to_string());
+        }
+        // The time may have run out since the clocks were last checked
+        self.check_game_over();
+        if self.result.is_some() {
+            return Err("The game is over".to_string());
+        }
+        if self.game.turn != color {
+            return Err("It is not your turn".to_string());
+        }
+        let chess_move = self.game.parse_move(text)?;
+        // SAN is sent as one word, so the en passant annotation is left out
+        let san = self.game.move_to_san(&chess_move).replace(" e.p.", "");
+        self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+        if let Some(clock) = self.clock.as_mut() {
+            let _ = clock.press();
+        }
+        let (white_ms, black_ms) = self.get_times();
+        self.broadcast(&ServerMessage::Moved { uci: chess_move.to_uci(), san, fen: self.game.to_fen(), white_ms, black_ms });
+        self.check_game_over();
+        return Ok(());
+    }
+}
+
+// A client connected to the server, and the game it is in
+struct Connection {
+    id: u64,
+    sender: Sender<ServerMessage>,
+    code: Option<String>,
+    color: Option<ChessPieceColor>, // None for spectators
+}
+
+// All games on the server
+#[derive(Default)]
+struct Lobby {
+    games: HashMap<String, ServerGame>,
+    counter: u64,
+}
+
+impl Lobby {
+    // Get a random number, hashing a counter with the random keys of the standard library
+    fn get_random(&mut self) -> u64 {
+        self.counter += 1;
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(self.counter);
+        return hasher.finish();
+    }
+
+    fn get_new_code(&mut self) -> String {
+        loop {
+            let mut number = self.get_random();
+            let mut code = String::new();
+            for _ in 0..CODE_LENGTH {
+                code.push(CODE_LETTERS[(number % CODE_LETTERS.len() as u64) as usize] as char);
+                number /= CODE_LETTERS.len() as u64;
+            }
+            if !self.games.contains_key(&code) {
+                return code;
+            }
+        }
+    }
+
+    fn get_new_token(&mut self) -> String {
+        return format!("{:016x}", self.get_random());
+    }
+
+    fn handle(&mut self, connection: &mut Connection, message: ClientMessage) {
+        let reply = match message {
+            ClientMessage::Create { .. } | ClientMessage::Join { .. } | ClientMessage::Watch { .. } | ClientMessage::Resume { .. }
+                if connection.code.is_some() => Err("Already in a game".to_string()),
+            ClientMessage::Create { color, time_control } => self.create(connection, color, time_control),
+            ClientMessage::Join { code } => self.join(connection, code),
+            ClientMessage::Watch { code } => self.watch(connection, code),
+            ClientMessage::Resume { code, token } => self.resume(connection, code, token),
+            ClientMessage::Move { text } => match self.get_seat(connection) {
+                Ok((game, color)) => game.play(color, &text),
+                Err(error) => Err(error),
+            },
+            ClientMessage::Resign => match self.get_seat(connection) {
+                Ok((game, _)) if game.result.is_some() => Err("The game is over".to_string()),
+                Ok((game, color)) => {
+                    let result = if color == ChessPieceColor::White { "0-1" } else { "1-0" };
+                    game.end(result, "resignation");
+                    Ok(())
+                }
+                Err(error) => Err(error),
+            },
+            ClientMessage::State => match connection.code.as_ref().and_then(|code| self.games.get(code)) {
+                Some(game) => {
+                    let _ = connection.sender.send(game.get_state());
+                    Ok(())
+                }
+                None => Err("Not in a game".to_string()),
+            },
+        };
+        if let Err(error) = reply {
+            let _ = connection.sender.send(get_error(&error));
+        }
+    }
+
+    // Get the game of a connection that plays in it, and the color it plays
+    fn get_seat(&mut self, connection: &Connection) -> Result<(&mut ServerGame, ChessPieceColor), String> {
+        let code = connection.code.as_ref().ok_or("Not in a game")?;
+        let game = self.games.get_mut(code).ok_or("Not in a game")?;
+        let color = connection.color.ok_or("Spectators can not play")?;
+        // After a resume only the new connection plays for the seat
+        let seat = game.seats[get_index(color)].as_ref().unwrap();
+        if seat.connection.as_ref().map(|(id, _)| *id) != Some(connection.id) {
+            return Err("The seat has been resumed from another connection".to_string());
+        }
+        return Ok((game, color));
+    }
+
+    fn create(&mut self, connection: &mut Connection, color: Option<ChessPieceColor>, time_control: Option<String>) -> Result<(), String> {
+        let clock = match time_control {
+            Some(text) => Some(Clock::with_system_time(text.parse::<TimeControl>()?)),
+            None => None,
+        };
+        let color = match color {
+            Some(color) => color,
+            None if self.get_random() % 2 == 0 => ChessPieceColor::White,
+            None => ChessPieceColor::Black,
+        };
+        let code = self.get_new_code();
+        let token = self.get_new_token();
+        let mut game = Game::new();
+        game.set_up_board();
+        let mut server_game = ServerGame { game, clock, seats: [None, None], spectators: Vec::new(), result: None, unused_since: None };
+        server_game.seats[get_index(color)] = Some(Seat {
+            token: token.clone(),
+            connection: Some((connection.id, connection.sender.clone())),
+        });
+        self.games.insert(code.clone(), server_game);
+        connection.code = Some(code.clone());
+        connection.color = Some(color);
+        let _ = connection.sender.send(ServerMessage::Created { code, color, token });
+        return Ok(());
+    }
+
+    fn join(&mut self, connection: &mut Connection, code: String) -> Result<(), String> {
+        let token = self.get_new_token();
+        let game = self.games.get_mut(&code).ok_or("No game with that code")?;
+        let index = game.seats.iter().position(|seat| seat.is_none()).ok_or("The game is full, watch it instead")?;
+        let color = if index == 0 { ChessPieceColor::White } else { ChessPieceColor::Black };
+        game.seats[index] = Some(Seat {
+            token: token.clone(),
+            connection: Some((connection.id, connection.sender.clone())),
+        });
+        connection.code = Some(code.clone());
+        connection.color = Some(color);
+        let _ = connection.sender.send(ServerMessage::Joined { code, color, token });
+
+        // Both players are here, start the game
+        if let Some(clock) = game.clock.as_mut() {
+            clock.start(game.game.turn);
+        }
+        game.broadcast(&game.get_state());
+        return Ok(());
+    }
+
+    fn watch(&mut self, connection: &mut Connection, code: String) -> Result<(), String> {
+        let game = self.games.get_mut(&code).ok_or("No game with that code")?;
+        game.spectators.push((connection.id, connection.sender.clone()));
+        connection.code = Some(code.clone());
+        let _ = connection.sender.send(ServerMessage::Watching { code });
+        let _ = connection.sender.send(game.get_state());
+        return Ok(());
+    }
+
+    fn resume(&mut self, connection: &mut Connection, code: String, token: String) -> Result<(), String> {
+        let game = self.games.get_mut(&code).ok_or("No game with that code")?;
+        let index = game.seats.iter()
+            .position(|seat| seat.as_ref().map(|seat| seat.token == token).unwrap_or(false))
+            .ok_or("Invalid token")?;
+        let color = if index == 0 { ChessPieceColor::White } else { ChessPieceColor::Black };
+        game.seats[index].as_mut().unwrap().connection = Some((connection.id, connection.sender.clone()));
+        connection.code = Some(code.clone());
+        connection.color = Some(color);
+        let _ = connection.sender.send(ServerMessage::Resumed { code, color });
+        let _ = connection.sender.send(game.get_state());
+        if let Some((result, reason)) = game.result.clone() {
+            let _ = connection.sender.send(ServerMessage::GameOver { result, reason });
+        }
+        game.send_to(get_opponent(color), ServerMessage::OpponentReturned);
+        return Ok(());
+    }
+
+    // Forget a connection that has closed. The seat is kept so the player can resume
+    fn disconnect(&mut self, connection: &Connection) {
+        let game = connection.code.as_ref().and_then(|code| self.games.get_mut(code));
+        if game.is_none() {
+            return;
+        }
+        let game = game.unwrap();
+        game.spectators.retain(|(id, _)| *id != connection.id);
+        if let Some(color) = connection.color {
+            let seat = game.seats[get_index(color)].as_mut().unwrap();
+            // The player may already have resumed from a new connection
+            if seat.connection.as_ref().map(|(id, _)| *id) == Some(connection.id) {
+                seat.connection = None;
+                game.send_to(get_opponent(color), ServerMessage::OpponentLeft);
+            }
+        }
+        self.remove_unused_games();
+    }
+
+    // Remove finished games nobody is connected to, and unfinished ones nobody has come back to for a while
+    fn remove_unused_games(&mut self) {
+        let now = Instant::now();
+        for game in self.games.values_mut() {
+            if !game.is_unused() {
+                game.unused_since = None;
+            }
+            else if game.unused_since.is_none() {
+                game.unused_since = Some(now);
+            }
+        }
+        self.games.retain(|_, game| match game.unused_since {
+            Some(since) => game.result.is_none() && now.duration_since(since) < ABANDONED_GAME_TIMEOUT,
+            None => true,
+        });
+    }
+
+    // End games where a player has run out of time
+    fn check_clocks(&mut self) {
+        for game in self.games.values_mut() {
+            if game.clock.is_some() && game.result.is_none() {
+                game.check_game_over();
+            }
+        }
+    }
+}
+
+// Hosts games for players on the local network. Clients connect over TCP and either send
+// lines such as "join ABCD", or open a WebSocket and send the same messages as JSON
+pub struct PlayServer {
+    listener: TcpListener,
+    lobby: Arc<Mutex<Lobby>>,
+}
+
+impl PlayServer {
+    // Listen on an address, ex "0.0.0.0:7878", or "127.0.0.1:0" for any free port
+    pub fn bind(address: &str) -> Result<PlayServer, String> {
+        let listener = TcpListener::bind(address).map_err(|error| error.to_string())?;
+        return Ok(PlayServer { listener, lobby: Arc::new(Mutex::new(Lobby::default())) });
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, String> {
+        return self.listener.local_addr().map_err(|error| error.to_string());
+    }
+
+    // Serve clients until the listener fails, each connection gets its own thread
+    pub fn run(self) -> Result<(), String> {
+        let lobby = self.lobby.clone();
+        thread::spawn(move || loop {
+            thread::sleep(CLOCK_CHECK_INTERVAL);
+            let mut lobby = lobby.lock().unwrap();
+            lobby.check_clocks();
+            lobby.remove_unused_games();
+        });
+
+        for (id, stream) in self.listener.incoming().enumerate() {
+            let stream = stream.map_err(|error| error.to_string())?;
+            let lobby = self.lobby.clone();
+            let id = id as u64;
+            thread::spawn(move || {
+                let _ = serve_connection(stream, lobby, id);
+            });
+        }
+        return Ok(());
+    }
+}
+
+fn serve_connection(stream: TcpStream, lobby: Arc<Mutex<Lobby>>, id: u64) -> io::Result<()> {
+    let (sender, receiver) = mpsc::channel();
+    let mut connection = Connection { id, sender, code: None, color: None };
+
+    // WebSocket connections start with an HTTP request, anything else is the line protocol
+    let mut start = [0; 4];
+    let mut length = stream.peek(&mut start)?;
+    while length < start.len() && length > 0 && !start[..length].contains(&b'\n') {
+        thread::sleep(WEBSOCKET_POLL_INTERVAL);
+        length = stream.peek(&mut start)?;
+    }
+    if &start == b"GET " {
+        let mut websocket = tungstenite::accept(stream).map_err(|error| io::Error::other(error.to_string()))?;
+        websocket.get_ref().set_read_timeout(Some(WEBSOCKET_POLL_INTERVAL))?;
+        loop {
+            match websocket.read() {
+                Ok(Message::Text(text)) => match serde_json::from_str::<ClientMessage>(&text) {
+                    Ok(message) => lobby.lock().unwrap().handle(&mut connection, message),
+                    Err(error) => {
+                        let _ = connection.sender.send(get_error(&error.to_string()));
+                    }
+                },
+                Ok(Message::Close(_)) => break,
+                Ok(_) => {}
+                Err(tungstenite::Error::Io(error))
+                    if error.kind() == io::ErrorKind::WouldBlock || error.kind() == io::ErrorKind::TimedOut => {}
+                Err(_) => break,
+            }
+            let mut failed = false;
+            while let Ok(message) = receiver.try_recv() {
+                if websocket.send(Message::Text(serde_json::to_string(&message).unwrap())).is_err() {
+                    failed = true;
+                    break;
+                }
+            }
+            if failed {
+                break;
+            }
+        }
+    }
+    else {
+        // Messages are written from their own thread, so events reach the client while it is idle
+        let mut writer = stream.try_clone()?;
+        thread::spawn(move || {
+            for message in receiver {
+                if writeln!(writer, "{}", message.to_line()).is_err() {
+                    break;
+                }
+            }
+        });
+        for line in BufReader::new(stream.try_clone()?).lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match ClientMessage::from_line(&line) {
+                Ok(message) => lobby.lock().unwrap().handle(&mut connection, message),
+                Err(error) => {
+                    let _ = connection.sender.send(get_error(&error));
+                }
+            }
+        }
+        let _ = stream.shutdown(std::net::Shutdown::Both);
+    }
+    lobby.lock().unwrap().disconnect(&connection);
+    return Ok(());
+}
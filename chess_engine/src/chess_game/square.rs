@@ -0,0 +1,291 @@
+use std::{convert::TryFrom, fmt, str::FromStr};
+
+use super::{BoardPosType, BoardPosition};
+
+// A column on the board, from a to h
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+
+impl File {
+    pub const ALL: [File; 8] = [File::A, File::B, File::C, File::D, File::E, File::F, File::G, File::H];
+
+    pub fn from_index(index: u8) -> Option<File> {
+        return File::ALL.get(index as usize).copied();
+    }
+
+    pub fn index(&self) -> u8 {
+        return *self as u8;
+    }
+
+    // Move a number of files to the right, or None if it ends up outside the board
+    pub fn offset(&self, d_file: i32) -> Option<File> {
+        let index = self.index() as i32 + d_file;
+        if !(0..8).contains(&index) {
+            return None;
+        }
+        return File::from_index(index as u8);
+    }
+
+    pub fn to_char(&self) -> char {
+        return (b'a' + self.index()) as char;
+    }
+
+    pub fn from_char(letter: char) -> Result<File, String> {
+        let index = BoardPosition::get_coordinate_from_letter(letter)?;
+        return Ok(File::ALL[index as usize]);
+    }
+}
+
+impl fmt::Display for File {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.to_char());
+    }
+}
+
+impl FromStr for File {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<File, String> {
+        let char_vec: Vec<char> = text.chars().collect();
+        if char_vec.len() != 1 {
+            return Err("Wrong size".to_string());
+        }
+        return File::from_char(char_vec[0]);
+    }
+}
+
+// A row on the board, from the first rank where white starts to the eighth rank where black starts
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 8] = [
+        Rank::First,
+        Rank::Second,
+        Rank::Third,
+        Rank::Fourth,
+        Rank::Fifth,
+        Rank::Sixth,
+        Rank::Seventh,
+        Rank::Eighth,
+    ];
+
+    pub fn from_index(index: u8) -> Option<Rank> {
+        return Rank::ALL.get(index as usize).copied();
+    }
+
+    pub fn index(&self) -> u8 {
+        return *self as u8;
+    }
+
+    // Move a number of ranks up towards the eighth rank, or None if it ends up outside the board
+    pub fn offset(&self, d_rank: i32) -> Option<Rank> {
+        let index = self.index() as i32 + d_rank;
+        if !(0..8).contains(&index) {
+            return None;
+        }
+        return Rank::from_index(index as u8);
+    }
+
+    pub fn to_char(&self) -> char {
+        return (b'1' + self.index()) as char;
+    }
+
+    pub fn from_char(number: char) -> Result<Rank, String> {
+        // Board positions count from the top of the board, so y = 0 is the eighth rank
+        let y = BoardPosition::get_coordinate_from_number(number)?;
+        return Ok(Rank::ALL[7 - y as usize]);
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}", self.to_char());
+    }
+}
+
+impl FromStr for Rank {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Rank, String> {
+        let char_vec: Vec<char> = text.chars().collect();
+        if char_vec.len() != 1 {
+            return Err("Wrong size".to_string());
+        }
+        return Rank::from_char(char_vec[0]);
+    }
+}
+
+// The eight directions a piece can move in, seen from white's side of the board
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    // Get the change in file and rank when taking one step in the direction
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::North => return (0, 1),
+            Direction::NorthEast => return (1, 1),
+            Direction::East => return (1, 0),
+            Direction::SouthEast => return (1, -1),
+            Direction::South => return (0, -1),
+            Direction::SouthWest => return (-1, -1),
+            Direction::West => return (-1, 0),
+            Direction::NorthWest => return (-1, 1),
+        }
+    }
+}
+
+// A square on the board, ordered a1, b1 ... h1, a2 ... h8
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Square {
+    A1, B1, C1, D1, E1, F1, G1, H1,
+    A2, B2, C2, D2, E2, F2, G2, H2,
+    A3, B3, C3, D3, E3, F3, G3, H3,
+    A4, B4, C4, D4, E4, F4, G4, H4,
+    A5, B5, C5, D5, E5, F5, G5, H5,
+    A6, B6, C6, D6, E6, F6, G6, H6,
+    A7, B7, C7, D7, E7, F7, G7, H7,
+    A8, B8, C8, D8, E8, F8, G8, H8,
+}
+
+impl Square {
+    pub const ALL: [Square; 64] = [
+        Square::A1, Square::B1, Square::C1, Square::D1, Square::E1, Square::F1, Square::G1, Square::H1,
+        Square::A2, Square::B2, Square::C2, Square::D2, Square::E2, Square::F2, Square::G2, Square::H2,
+        Square::A3, Square::B3, Square::C3, Square::D3, Square::E3, Square::F3, Square::G3, Square::H3,
+        Square::A4, Square::B4, Square::C4, Square::D4, Square::E4, Square::F4, Square::G4, Square::H4,
+        Square::A5, Square::B5, Square::C5, Square::D5, Square::E5, Square::F5, Square::G5, Square::H5,
+        Square::A6, Square::B6, Square::C6, Square::D6, Square::E6, Square::F6, Square::G6, Square::H6,
+        Square::A7, Square::B7, Square::C7, Square::D7, Square::E7, Square::F7, Square::G7, Square::H7,
+        Square::A8, Square::B8, Square::C8, Square::D8, Square::E8, Square::F8, Square::G8, Square::H8,
+    ];
+
+    pub fn new(file: File, rank: Rank) -> Square {
+        return Square::ALL[(file.index() + 8 * rank.index()) as usize];
+    }
+
+    pub fn from_index(index: u8) -> Option<Square> {
+        return Square::ALL.get(index as usize).copied();
+    }
+
+    pub fn index(&self) -> u8 {
+        return *self as u8;
+    }
+
+    pub fn file(&self) -> File {
+        return File::ALL[(self.index() % 8) as usize];
+    }
+
+    pub fn rank(&self) -> Rank {
+        return Rank::ALL[(self.index() / 8) as usize];
+    }
+
+    // Iterate all squares, from a1 to h8
+    pub fn all() -> impl Iterator<Item = Square> {
+        return Square::ALL.iter().copied();
+    }
+
+    // Move a number of files and ranks, or None if it ends up outside the board
+    pub fn offset(&self, d_file: i32, d_rank: i32) -> Option<Square> {
+        let file = self.file().offset(d_file)?;
+        let rank = self.rank().offset(d_rank)?;
+        return Some(Square::new(file, rank));
+    }
+
+    // Take one step in a direction, or None if it ends up outside the board
+    pub fn step(&self, direction: Direction) -> Option<Square> {
+        let (d_file, d_rank) = direction.offset();
+        return self.offset(d_file, d_rank);
+    }
+
+    // Iterate the squares along a direction, not including this square
+    pub fn ray(&self, direction: Direction) -> impl Iterator<Item = Square> {
+        let mut square = *self;
+        return std::iter::from_fn(move || {
+            square = square.step(direction)?;
+            return Some(square);
+        });
+    }
+
+    pub fn is_light(&self) -> bool {
+        return (self.file().index() + self.rank().index()) % 2 == 1;
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        return write!(f, "{}{}", self.file(), self.rank());
+    }
+}
+
+impl FromStr for Square {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Square, String> {
+        let char_vec: Vec<char> = text.chars().collect();
+        if char_vec.len() != 2 {
+            return Err("Wrong size".to_string());
+        }
+        return Ok(Square::new(File::from_char(char_vec[0])?, Rank::from_char(char_vec[1])?));
+    }
+}
+
+impl From<Square> for BoardPosition {
+    fn from(square: Square) -> BoardPosition {
+        return BoardPosition::new(square.file().index() as BoardPosType, 7 - square.rank().index() as BoardPosType);
+    }
+}
+
+impl TryFrom<BoardPosition> for Square {
+    type Error = String;
+
+    fn try_from(pos: BoardPosition) -> Result<Square, String> {
+        if pos.x > 7 || pos.y > 7 {
+            return Err("Outside of the board!".to_string());
+        }
+        return Ok(Square::new(File::ALL[pos.x as usize], Rank::ALL[7 - pos.y as usize]));
+    }
+}
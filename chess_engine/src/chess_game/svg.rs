@@ -0,0 +1,180 @@
+use std::fmt::Write;
+
+use super::{Arrow, BoardPosition, ChessPieceColor, ChessPieceId, Game, Highlight, MarkColor};
+
+// Each square is 45 units wide in the SVG, the pieces are drawn in a 45 by 45 box
+const SQUARE_SIZE: i32 = 45;
+
+const LIGHT_SQUARE_COLOR: &str = "#f0d9b5";
+const DARK_SQUARE_COLOR: &str = "#b58863";
+const LAST_MOVE_COLOR: &str = "#cdd26a";
+
+// Shapes of the pieces, filled with the color of the piece and outlined with the other color
+const PAWN_GLYPH: &str = r##"<circle cx="22.5" cy="15" r="6"/><path d="M17 36 L19 21 L26 21 L28 36 Z"/><rect x="11" y="35" width="23" height="5" rx="1"/>"##;
+const KNIGHT_GLYPH: &str = r##"<path d="M14 39 L31 39 C32 28 30 18 24 12 L22 7 L19 11 C15 13 11 18 9 23 L11 27 L15 25 L19 22 C19 28 15 32 14 39 Z"/><circle cx="17" cy="16" r="1.5" class="detail"/>"##;
+const BISHOP_GLYPH: &str = r##"<rect x="11" y="35" width="23" height="5" rx="1"/><path d="M15 35 C15 29 18 26 18 26 C14 23 14 16 22.5 9 C31 16 31 23 27 26 C27 26 30 29 30 35 Z"/><circle cx="22.5" cy="7" r="2.5"/><path d="M20 19 L25 19 M22.5 16.5 L22.5 21.5" class="detail"/>"##;
+const ROOK_GLYPH: &str = r##"<rect x="11" y="35" width="23" height="5" rx="1"/><path d="M15 35 L16 17 L29 17 L30 35 Z"/><path d="M12 9 L16 9 L16 12 L20 12 L20 9 L25 9 L25 12 L29 12 L29 9 L33 9 L33 17 L12 17 Z"/>"##;
+const QUEEN_GLYPH: &str = r##"<rect x="11" y="35" width="23" height="5" rx="1"/><path d="M13 35 L9 13 L16 25 L17 11 L22.5 24 L28 11 L29 25 L36 13 L32 35 Z"/><circle cx="9" cy="12" r="2.5"/><circle cx="17" cy="10" r="2.5"/><circle cx="28" cy="10" r="2.5"/><circle cx="36" cy="12" r="2.5"/>"##;
+const KING_GLYPH: &str = r##"<rect x="11" y="35" width="23" height="5" rx="1"/><path d="M13 35 C8 28 8 20 15 19 C19 18 22.5 22 22.5 25 C22.5 22 26 18 30 19 C37 20 37 28 32 35 Z"/><path d="M22.5 6 L22.5 17 M18 10 L27 10" class="detail"/>"##;
+
+// What to draw in a board diagram
+#[derive(Clone, Debug)]
+pub struct SvgOptions {
+    pub size: u32, // Width and height of the image in pixels
+    pub orientation: ChessPieceColor, // The side shown at the bottom of the board
+    pub coordinates: bool,
+    pub arrows: Vec<Arrow>,
+    pub highlights: Vec<Highlight>,
+    pub mark_last_move: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        return Self {
+            size: 360,
+            orientation: ChessPieceColor::White,
+            coordinates: true,
+            arrows: Vec::new(),
+            highlights: Vec::new(),
+            mark_last_move: true,
+        };
+    }
+}
+
+impl SvgOptions {
+    // Get the column and row a square is drawn in, counted from the top left corner
+    fn get_screen_pos(&self, pos: BoardPosition) -> (i32, i32) {
+        match self.orientation {
+            ChessPieceColor::White => return (pos.x as i32, pos.y as i32),
+            ChessPieceColor::Black => return (7 - pos.x as i32, 7 - pos.y as i32),
+        }
+    }
+
+    // Get the center of a square in SVG units
+    fn get_center(&self, pos: BoardPosition) -> (f32, f32) {
+        let (column, row) = self.get_screen_pos(pos);
+        return (
+            (column * SQUARE_SIZE) as f32 + SQUARE_SIZE as f32 / 2.0,
+            (row * SQUARE_SIZE) as f32 + SQUARE_SIZE as f32 / 2.0,
+        );
+    }
+}
+
+fn get_mark_color(color: MarkColor) -> &'static str {
+    match color {
+        MarkColor::Green => return "#15781b",
+        MarkColor::Red => return "#882020",
+        MarkColor::Yellow => return "#e68f00",
+        MarkColor::Blue => return "#003088",
+    }
+}
+
+fn get_glyph(id: ChessPieceId) -> &'static str {
+    match id {
+        ChessPieceId::Pawn => return PAWN_GLYPH,
+        ChessPieceId::Knight => return KNIGHT_GLYPH,
+        ChessPieceId::Bishop => return BISHOP_GLYPH,
+        ChessPieceId::Rook => return ROOK_GLYPH,
+        ChessPieceId::Queen => return QUEEN_GLYPH,
+        ChessPieceId::King => return KING_GLYPH,
+    }
+}
+
+impl Game {
+    // Draw the board as a standalone SVG image, with the pieces drawn from vector shapes in the image itself
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let mut svg = String::new();
+        let board_size = 8 * SQUARE_SIZE;
+        writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" version="1.1" width="{0}" height="{0}" viewBox="0 0 {1} {1}">"#, options.size, board_size).unwrap();
+
+        // Shapes of the pieces and arrow heads, used further down
+        writeln!(svg, "<defs>").unwrap();
+        for (color, letter, fill, stroke) in [
+            (ChessPieceColor::White, 'w', "#ffffff", "#000000"),
+            (ChessPieceColor::Black, 'b', "#000000", "#ffffff"),
+        ] {
+            for id in [ChessPieceId::King, ChessPieceId::Queen, ChessPieceId::Rook,
+                ChessPieceId::Bishop, ChessPieceId::Knight, ChessPieceId::Pawn] {
+                // Black pieces are outlined in black too, only the details are drawn in white
+                let outline = if color == ChessPieceColor::White { stroke } else { fill };
+                writeln!(svg, r#"<g id="{}{}" fill="{}" stroke="{}" stroke-width="1.5" stroke-linejoin="round">{}</g>"#,
+                    letter, id.to_uci_letter().to_ascii_uppercase(), fill, outline,
+                    get_glyph(id).replace(r#"class="detail""#, format!(r#"fill="none" stroke="{}""#, stroke).as_str())).unwrap();
+            }
+        }
+        for color in [MarkColor::Green, MarkColor::Red, MarkColor::Yellow, MarkColor::Blue] {
+            writeln!(svg, r#"<marker id="arrowhead-{}" viewBox="0 0 10 10" refX="5" refY="5" markerWidth="4" markerHeight="4" orient="auto"><path d="M0 0 L10 5 L0 10 Z" fill="{}"/></marker>"#,
+                color.to_char(), get_mark_color(color)).unwrap();
+        }
+        writeln!(svg, "</defs>").unwrap();
+
+        // Squares, with the last move shaded and highlights on top
+        let last_move = if options.mark_last_move { self.last_move() } else { None };
+        for x in 0..8 {
+            for y in 0..8 {
+                let pos = BoardPosition::new(x, y);
+                let (column, row) = options.get_screen_pos(pos);
+                let mut fill = if (x + y) % 2 == 0 { LIGHT_SQUARE_COLOR } else { DARK_SQUARE_COLOR };
+                if last_move.is_some() && (last_move.unwrap().from_pos == pos || last_move.unwrap().to_pos == pos) {
+                    fill = LAST_MOVE_COLOR;
+                }
+                writeln!(svg, r#"<rect x="{}" y="{}" width="{size}" height="{size}" fill="{}"/>"#,
+                    column * SQUARE_SIZE, row * SQUARE_SIZE, fill, size = SQUARE_SIZE).unwrap();
+            }
+        }
+        for highlight in &options.highlights {
+            let (column, row) = options.get_screen_pos(highlight.pos);
+            writeln!(svg, r#"<rect x="{}" y="{}" width="{size}" height="{size}" fill="{}" fill-opacity="0.5"/>"#,
+                column * SQUARE_SIZE, row * SQUARE_SIZE, get_mark_color(highlight.color), size = SQUARE_SIZE).unwrap();
+        }
+
+        // Coordinates in the corners of the squares along the left and bottom edges
+        if options.coordinates {
+            for i in 0..8 {
+                let file_pos = BoardPosition::new(i, if options.orientation == ChessPieceColor::White { 7 } else { 0 });
+                let rank_pos = BoardPosition::new(if options.orientation == ChessPieceColor::White { 0 } else { 7 }, i);
+                let (column, _) = options.get_screen_pos(file_pos);
+                let (_, row) = options.get_screen_pos(rank_pos);
+                let file_fill = if (file_pos.x + file_pos.y) % 2 == 0 { DARK_SQUARE_COLOR } else { LIGHT_SQUARE_COLOR };
+                let rank_fill = if (rank_pos.x + rank_pos.y) % 2 == 0 { DARK_SQUARE_COLOR } else { LIGHT_SQUARE_COLOR };
+                writeln!(svg, r#"<text x="{}" y="{}" font-family="sans-serif" font-size="9" text-anchor="end" fill="{}">{}</text>"#,
+                    (column + 1) * SQUARE_SIZE - 2, board_size - 2, file_fill,
+                    BoardPosition::get_letter_from_coordinate(i).unwrap()).unwrap();
+                writeln!(svg, r#"<text x="2" y="{}" font-family="sans-serif" font-size="9" fill="{}">{}</text>"#,
+                    row * SQUARE_SIZE + 9, rank_fill, BoardPosition::get_number_from_coordinate(i).unwrap()).unwrap();
+            }
+        }
+
+        // Pieces
+        for y in 0..8 {
+            for x in 0..8 {
+                let pos = BoardPosition::new(x, y);
+                let piece = self.piece_at(pos);
+                if piece.is_none() {
+                    continue;
+                }
+                let (column, row) = options.get_screen_pos(pos);
+                let color_letter = if piece.unwrap().color == ChessPieceColor::White { 'w' } else { 'b' };
+                writeln!(svg, r##"<use xlink:href="#{}{}" x="{}" y="{}"/>"##, color_letter,
+                    piece.unwrap().id.to_uci_letter().to_ascii_uppercase(), column * SQUARE_SIZE, row * SQUARE_SIZE).unwrap();
+            }
+        }
+
+        // Arrows, ending a bit before the center of the target square so the arrow head does not cover the piece
+        for arrow in &options.arrows {
+            let (from_x, from_y) = options.get_center(arrow.from_pos);
+            let (to_x, to_y) = options.get_center(arrow.to_pos);
+            let length = ((to_x - from_x).powi(2) + (to_y - from_y).powi(2)).sqrt();
+            if length == 0.0 {
+                continue;
+            }
+            let shorten = SQUARE_SIZE as f32 * 0.3 / length;
+            writeln!(svg, r#"<line x1="{:.1}" y1="{:.1}" x2="{:.1}" y2="{:.1}" stroke="{}" stroke-width="8" stroke-linecap="round" stroke-opacity="0.8" marker-end="url(#arrowhead-{})"/>"#,
+                from_x, from_y, to_x - (to_x - from_x) * shorten, to_y - (to_y - from_y) * shorten,
+                get_mark_color(arrow.color), arrow.color.to_char()).unwrap();
+        }
+
+        writeln!(svg, "</svg>").unwrap();
+        return svg;
+    }
+}
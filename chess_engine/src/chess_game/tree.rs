@@ -0,0 +1,396 @@
+use super::{BoardPosition, ChessPieceColor, Game, Move};
+
+// Used to find a node in a game tree
+pub type NodeId = usize;
+
+// The colors used for arrows and highlighted squares, the same as in the PGN %cal and %csl commands
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MarkColor {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+}
+
+impl MarkColor {
+    pub fn to_char(&self) -> char {
+        match self {
+            MarkColor::Green => return 'G',
+            MarkColor::Red => return 'R',
+            MarkColor::Yellow => return 'Y',
+            MarkColor::Blue => return 'B',
+        }
+    }
+
+    pub fn from_char(letter: char) -> Result<MarkColor, String> {
+        match letter {
+            'G' => return Ok(MarkColor::Green),
+            'R' => return Ok(MarkColor::Red),
+            'Y' => return Ok(MarkColor::Yellow),
+            'B' => return Ok(MarkColor::Blue),
+            _ => return Err(format!("Unknown mark color '{}'", letter)),
+        }
+    }
+}
+
+// An arrow drawn on the board, ex to show a plan
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Arrow {
+    pub from_pos: BoardPosition,
+    pub to_pos: BoardPosition,
+    pub color: MarkColor,
+}
+
+// A square marked with a color
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Highlight {
+    pub pos: BoardPosition,
+    pub color: MarkColor,
+}
+
+// A position in the tree, together with the move that led to it and its annotations
+#[derive(Clone)]
+pub struct GameNode {
+    game: Game,
+    chess_move: Option<Move>, // None for the root
+    san: Option<String>,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>, // The first child is the main line, the rest are variations
+    pub comment: Option<String>,
+    pub nags: Vec<u8>, // Numeric annotation glyphs, ex 1 for ! and 2 for ?
+    pub arrows: Vec<Arrow>,
+    pub highlights: Vec<Highlight>,
+}
+
+impl GameNode {
+    fn new(game: Game, chess_move: Option<Move>, san: Option<String>, parent: Option<NodeId>) -> Self {
+        return Self {
+            game,
+            chess_move,
+            san,
+            parent,
+            children: Vec::new(),
+            comment: None,
+            nags: Vec::new(),
+            arrows: Vec::new(),
+            highlights: Vec::new(),
+        };
+    }
+
+    // Get the position after the move of this node
+    pub fn game(&self) -> &Game {
+        return &self.game;
+    }
+
+    pub fn chess_move(&self) -> Option<Move> {
+        return self.chess_move;
+    }
+
+    pub fn san(&self) -> Option<&str> {
+        return self.san.as_deref();
+    }
+
+    pub fn parent(&self) -> Option<NodeId> {
+        return self.parent;
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        return &self.children;
+    }
+}
+
+// A game with variations, used for analysis. Moves are played with Game::move_piece on a copy of the position
+#[derive(Clone)]
+pub struct GameTree {
+    nodes: Vec<Option<GameNode>>, // Deleted nodes are set to None so the ids of other nodes stay the same
+    current: NodeId,
+}
+
+impl GameTree {
+    // Start a tree from the current position of a game
+    pub fn new(game: Game) -> Self {
+        return Self {
+            nodes: vec![Some(GameNode::new(game, None, None, None))],
+            current: 0,
+        };
+    }
+
+    pub fn root(&self) -> NodeId {
+        return 0;
+    }
+
+    pub fn current(&self) -> NodeId {
+        return self.current;
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&GameNode> {
+        return self.nodes.get(id).and_then(|node| node.as_ref());
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut GameNode> {
+        return self.nodes.get_mut(id).and_then(|node| node.as_mut());
+    }
+
+    pub fn current_node(&self) -> &GameNode {
+        return self.node(self.current).unwrap();
+    }
+
+    pub fn current_node_mut(&mut self) -> &mut GameNode {
+        let current = self.current;
+        return self.node_mut(current).unwrap();
+    }
+
+    // Get the position at the current node
+    pub fn game(&self) -> &Game {
+        return self.current_node().game();
+    }
+
+    // Play a move from the current node and go to the new node.
+    // If the move has already been played from here, go to that node instead of adding a new variation
+    pub fn play_move(&mut self, chess_move: Move) -> Result<NodeId, String> {
+        for &child in self.current_node().children() {
+            if self.node(child).unwrap().chess_move().unwrap().to_uci() == chess_move.to_uci() {
+                self.current = child;
+                return Ok(child);
+            }
+        }
+        let mut game = self.game().clone();
+        let san = game.move_to_san(&chess_move);
+        game.move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+
+        let id = self.nodes.len();
+        self.nodes.push(Some(GameNode::new(game, Some(chess_move), Some(san), Some(self.current))));
+        self.current_node_mut().children.push(id);
+        self.current = id;
+        return Ok(id);
+    }
+
+    // Play a move in standard algebraic notation, ex "Nf3"
+    pub fn play_san(&mut self, text: &str) -> Result<NodeId, String> {
+        let chess_move = self.game().parse_san(text)?;
+        return self.play_move(chess_move);
+    }
+
+    // Go one move forward along the main line, returns false if there are no more moves
+    pub fn forward(&mut self) -> bool {
+        let children = self.current_node().children();
+        if children.is_empty() {
+            return false;
+        }
+        self.current = children[0];
+        return true;
+    }
+
+    // Go one move back, returns false at the start of the game
+    pub fn back(&mut self) -> bool {
+        match self.current_node().parent() {
+            Some(parent) => {
+                self.current = parent;
+                return true;
+            }
+            None => return false,
+        }
+    }
+
+    pub fn go_to(&mut self, id: NodeId) -> Result<(), String> {
+        if self.node(id).is_none() {
+            return Err("No such node".to_string());
+        }
+        self.current = id;
+        return Ok(());
+    }
+
+    pub fn go_to_start(&mut self) {
+        self.current = self.root();
+    }
+
+    // Go to the end of the line the current node is in
+    pub fn go_to_end(&mut self) {
+        while self.forward() {}
+    }
+
+    // Get the nodes of the main line, not including the root
+    pub fn main_line(&self) -> Vec<NodeId> {
+        let mut line = Vec::new();
+        let mut node = self.node(self.root()).unwrap();
+        while !node.children.is_empty() {
+            line.push(node.children[0]);
+            node = self.node(node.children[0]).unwrap();
+        }
+        return line;
+    }
+
+    // Get the nodes from the root to a node, not including the root
+    pub fn path_to(&self, id: NodeId) -> Result<Vec<NodeId>, String> {
+        if self.node(id).is_none() {
+            return Err("No such node".to_string());
+        }
+        let mut path = Vec::new();
+        let mut current = id;
+        while let Some(parent) = self.node(current).unwrap().parent() {
+            path.push(current);
+            current = parent;
+        }
+        path.reverse();
+        return Ok(path);
+    }
+
+    // Make the line leading to a node the main line
+    pub fn promote_to_main_line(&mut self, id: NodeId) -> Result<(), String> {
+        for node_id in self.path_to(id)? {
+            let parent = self.node(node_id).unwrap().parent().unwrap();
+            let children = &mut self.node_mut(parent).unwrap().children;
+            let index = children.iter().position(|&child| child == node_id).unwrap();
+            let node_id = children.remove(index);
+            children.insert(0, node_id);
+        }
+        return Ok(());
+    }
+
+    // Remove a node and all moves after it. If the current node is removed, the parent becomes the current node
+    pub fn delete_variation(&mut self, id: NodeId) -> Result<(), String> {
+        if self.node(id).is_none() {
+            return Err("No such node".to_string());
+        }
+        let parent = match self.node(id).unwrap().parent() {
+            Some(parent) => parent,
+            None => return Err("Can not delete the start of the game".to_string()),
+        };
+        if self.path_to(self.current)?.contains(&id) {
+            self.current = parent;
+        }
+        self.node_mut(parent).unwrap().children.retain(|&child| child != id);
+
+        let mut to_delete = vec![id];
+        while let Some(node_id) = to_delete.pop() {
+            let node = self.nodes[node_id].take().unwrap();
+            to_delete.extend(node.children);
+        }
+        return Ok(());
+    }
+
+    // Get the game in Portable Game Notation, with variations in parentheses
+    pub fn to_pgn(&self) -> String {
+        return self.to_pgn_with_tags(&[]);
+    }
+
+    // Get the game in PGN with tags written before the result, ex ("White", "Magnus")
+    pub fn to_pgn_with_tags(&self, tags: &[(&str, &str)]) -> String {
+        let root = self.node(self.root()).unwrap();
+        let mut pgn = String::new();
+        let start_fen = root.game().to_fen();
+        let result = self.get_result();
+        for (name, value) in tags {
+            pgn += format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")).as_str();
+        }
+        pgn += format!("[Result \"{}\"]\n", result).as_str();
+        if start_fen != "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1" {
+            pgn += format!("[SetUp \"1\"]\n[FEN \"{}\"]\n", start_fen).as_str();
+        }
+        pgn.push('\n');
+
+        let mut movetext: Vec<String> = Vec::new();
+        let annotation = Self::get_pgn_comment(root);
+        if !annotation.is_empty() {
+            movetext.push(annotation);
+        }
+        self.write_pgn_line(self.root(), true, &mut movetext);
+        movetext.push(result.to_string());
+        pgn += movetext.join(" ").as_str();
+        pgn.push('\n');
+        return pgn;
+    }
+
+    // Write the moves after a node, with the variations of each move
+    fn write_pgn_line(&self, id: NodeId, mut write_number: bool, movetext: &mut Vec<String>) {
+        let mut node = self.node(id).unwrap();
+        while !node.children.is_empty() {
+            let main = node.children[0];
+            self.write_pgn_move(node, main, write_number, movetext);
+            write_number = false;
+
+            for &variation in &node.children[1..] {
+                let mut variation_text: Vec<String> = Vec::new();
+                self.write_pgn_move(node, variation, true, &mut variation_text);
+                self.write_pgn_line(variation, false, &mut variation_text);
+                movetext.push(format!("({})", variation_text.join(" ")));
+                write_number = true;
+            }
+
+            let main_node = self.node(main).unwrap();
+            if main_node.comment.is_some() || !main_node.arrows.is_empty() || !main_node.highlights.is_empty() {
+                write_number = true;
+            }
+            node = main_node;
+        }
+    }
+
+    // Write a move with its number, annotation glyphs and comment
+    fn write_pgn_move(&self, parent: &GameNode, id: NodeId, write_number: bool, movetext: &mut Vec<String>) {
+        let node = self.node(id).unwrap();
+        let game = parent.game();
+        let mut text = String::new();
+        if game.turn() == ChessPieceColor::White {
+            text += format!("{}. ", game.fullmove_number()).as_str();
+        }
+        else if write_number {
+            text += format!("{}... ", game.fullmove_number()).as_str();
+        }
+        // PGN does not mark en passant captures
+        text += node.san().unwrap().trim_end_matches(" e.p.");
+        movetext.push(text);
+        for nag in &node.nags {
+            movetext.push(format!("${}", nag));
+        }
+        let annotation = Self::get_pgn_comment(node);
+        if !annotation.is_empty() {
+            movetext.push(annotation);
+        }
+    }
+
+    // Get the comment of a node with its arrows and highlights, ex "{[%cal Ge2e4] Good move}"
+    fn get_pgn_comment(node: &GameNode) -> String {
+        let mut parts: Vec<String> = Vec::new();
+        if !node.highlights.is_empty() {
+            let squares: Vec<String> = node.highlights.iter()
+                .map(|highlight| format!("{}{}", highlight.color.to_char(), highlight.pos.to_algebraic_notation()))
+                .collect();
+            parts.push(format!("[%csl {}]", squares.join(",")));
+        }
+        if !node.arrows.is_empty() {
+            let arrows: Vec<String> = node.arrows.iter()
+                .map(|arrow| format!("{}{}{}", arrow.color.to_char(),
+                    arrow.from_pos.to_algebraic_notation(), arrow.to_pos.to_algebraic_notation()))
+                .collect();
+            parts.push(format!("[%cal {}]", arrows.join(",")));
+        }
+        if node.comment.is_some() {
+            // Braces end the comment in PGN, so they can not be part of it
+            parts.push(node.comment.clone().unwrap().replace('}', ")").replace('{', "("));
+        }
+        if parts.is_empty() {
+            return String::new();
+        }
+        return format!("{{{}}}", parts.join(" "));
+    }
+
+    // Get the result at the end of the main line, "*" if the game is not over
+    fn get_result(&self) -> &'static str {
+        let mut game = self.node(self.root()).unwrap().game();
+        let main_line = self.main_line();
+        if !main_line.is_empty() {
+            game = self.node(*main_line.last().unwrap()).unwrap().game();
+        }
+        if !game.game_is_over() {
+            return "*";
+        }
+        match game.get_winner() {
+            Some(ChessPieceColor::White) => return "1-0",
+            Some(ChessPieceColor::Black) => return "0-1",
+            None => return "1/2-1/2",
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use wasm_bindgen::prelude::*;
+
+use super::{ChessPieceColor, Game, SvgOptions};
+
+// A game for JavaScript, with moves and colors passed as strings. Errors are thrown as strings
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame {
+    game: Game,
+}
+
+fn get_color_name(color: ChessPieceColor) -> String {
+    match color {
+        ChessPieceColor::White => return "white".to_string(),
+        ChessPieceColor::Black => return "black".to_string(),
+    }
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    // Start a game from the standard position
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> WasmGame {
+        let mut game = Game::new();
+        game.set_up_board();
+        return WasmGame { game };
+    }
+
+    #[wasm_bindgen(js_name = fromFen)]
+    pub fn from_fen(fen: &str) -> Result<WasmGame, String> {
+        return Ok(WasmGame { game: Game::from_fen(fen)? });
+    }
+
+    pub fn fen(&self) -> String {
+        return self.game.to_fen();
+    }
+
+    // The legal moves in UCI notation, ex "e2e4" and "e7e8q"
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        return self.game.legal_moves().iter().map(|chess_move| chess_move.to_uci()).collect();
+    }
+
+    // The legal moves in algebraic notation, in the same order as legalMoves
+    #[wasm_bindgen(js_name = legalMovesSan)]
+    pub fn legal_moves_san(&self) -> Vec<String> {
+        return self.game.legal_moves().iter().map(|chess_move| self.game.move_to_san(chess_move)).collect();
+    }
+
+    // Play a move in UCI or algebraic notation, returns the move in algebraic notation
+    #[wasm_bindgen(js_name = makeMove)]
+    pub fn make_move(&mut self, text: &str) -> Result<String, String> {
+        let chess_move = self.game.parse_move(text)?;
+        let san = self.game.move_to_san(&chess_move);
+        self.game.move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+        return Ok(san);
+    }
+
+    // Take back the last move, returns it in algebraic notation
+    pub fn undo(&mut self) -> Result<String, String> {
+        return Ok(self.game.undo_move()?.san);
+    }
+
+    // The moves played in algebraic notation
+    pub fn history(&self) -> Vec<String> {
+        return self.game.history().iter().map(|entry| entry.san.clone()).collect();
+    }
+
+    // "white" or "black"
+    pub fn turn(&self) -> String {
+        return get_color_name(self.game.turn);
+    }
+
+    #[wasm_bindgen(js_name = isCheck)]
+    pub fn is_check(&self) -> bool {
+        return self.game.is_check().is_some();
+    }
+
+    #[wasm_bindgen(js_name = isCheckmate)]
+    pub fn is_checkmate(&self) -> bool {
+        return self.game.is_check_mate();
+    }
+
+    #[wasm_bindgen(js_name = isStalemate)]
+    pub fn is_stalemate(&self) -> bool {
+        return self.game.is_stale_mate();
+    }
+
+    #[wasm_bindgen(js_name = isGameOver)]
+    pub fn is_game_over(&self) -> bool {
+        return self.game.game_is_over();
+    }
+
+    // The result as in PGN, "1-0", "0-1", "1/2-1/2", or "*" while the game goes on
+    pub fn outcome(&self) -> String {
+        match self.game.get_winner() {
+            Some(ChessPieceColor::White) => return "1-0".to_string(),
+            Some(ChessPieceColor::Black) => return "0-1".to_string(),
+            None if self.game.game_is_over() => return "1/2-1/2".to_string(),
+            None => return "*".to_string(),
+        }
+    }
+
+    // Draw the board as SVG, with "white" or "black" at the bottom
+    #[wasm_bindgen(js_name = toSvg)]
+    pub fn to_svg(&self, orientation: &str, size: u32) -> Result<String, String> {
+        let orientation = match orientation {
+            "white" => ChessPieceColor::White,
+            "black" => ChessPieceColor::Black,
+            _ => return Err(format!("Invalid orientation '{}'", orientation)),
+        };
+        return Ok(self.game.to_svg(&SvgOptions { orientation, size, ..SvgOptions::default() }));
+    }
+
+    #[wasm_bindgen(js_name = toPgn)]
+    pub fn to_pgn(&self) -> String {
+        return self.game.to_pgn();
+    }
+
+    #[wasm_bindgen(js_name = fromPgn)]
+    pub fn from_pgn(pgn: &str) -> Result<WasmGame, String> {
+        return Ok(WasmGame { game: Game::from_pgn(pgn)? });
+    }
+}
@@ -1,5 +1,56 @@
 pub mod chess_game {
-    use std::{collections::LinkedList};
+    use std::{collections::LinkedList, sync::Arc};
+
+    #[cfg(feature = "http")]
+    mod api;
+    mod castling;
+    mod clock;
+    mod command;
+    mod encoding;
+    mod fen;
+    #[cfg(feature = "ffi")]
+    mod ffi;
+    mod history;
+    mod pgn;
+    mod position;
+    #[cfg(feature = "server")]
+    mod protocol;
+    #[cfg(feature = "python")]
+    mod python;
+    mod render;
+    mod search;
+    #[cfg(feature = "server")]
+    mod server;
+    #[cfg(feature = "serde")]
+    mod serialize;
+    mod square;
+    mod svg;
+    mod tree;
+    #[cfg(feature = "wasm")]
+    mod wasm;
+    #[cfg(feature = "http")]
+    pub use api::*;
+    pub use castling::*;
+    pub use clock::*;
+    pub use command::*;
+    pub use encoding::*;
+    #[cfg(feature = "ffi")]
+    pub use ffi::*;
+    pub use history::HistoryEntry;
+    pub use position::*;
+    #[cfg(feature = "server")]
+    pub use protocol::*;
+    #[cfg(feature = "python")]
+    pub use python::*;
+    pub use render::*;
+    pub use search::*;
+    #[cfg(feature = "server")]
+    pub use server::*;
+    pub use square::*;
+    pub use svg::*;
+    pub use tree::*;
+    #[cfg(feature = "wasm")]
+    pub use wasm::*;
 
     #[derive(Clone, Copy)]
     pub enum ColorTerminal {
@@ -12,15 +63,23 @@ pub mod chess_game {
         LightBlue,
         White,
     }
+    #[cfg(feature = "terminal")]
     pub fn print_color(text: &str, text_color: ColorTerminal, background_color: ColorTerminal) {
-        //let background_mod_8 = background_color as i32 % 8;
+        let mut colored_text = String::new();
+        write_color(&mut colored_text, text, text_color, background_color).unwrap();
+        print!("{}", colored_text);
+    }
+
+    // Write text with ANSI escape codes for the colors
+    pub fn write_color(out: &mut dyn std::fmt::Write, text: &str, text_color: ColorTerminal, background_color: ColorTerminal) -> std::fmt::Result {
         let text_mod_8 = text_color as i32 % 8;
         let background_mod_8 = background_color as i32 % 8;
-        print!("\x1b[{}m\x1b[{}m{}\x1b[m", 40+background_mod_8, 30+text_mod_8 as i32, text);
+        return write!(out, "\x1b[{}m\x1b[{}m{}\x1b[m", 40+background_mod_8, 30+text_mod_8, text);
     }
 
     type BoardPosType = u8;
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct BoardPosition {
         x: BoardPosType,
         y: BoardPosType
@@ -69,9 +128,31 @@ pub mod chess_game {
                 _ => return Err("Could not parse coordinate number".to_string())
             }
         }
+
+        pub fn to_algebraic_notation(&self) -> String {
+            let mut text = String::new();
+            text.push(Self::get_letter_from_coordinate(self.x).unwrap_or('?'));
+            text.push(Self::get_number_from_coordinate(self.y).unwrap_or('?'));
+            return text;
+        }
+
+        pub fn get_letter_from_coordinate(x: BoardPosType) -> Result<char, String> {
+            if x > 7 {
+                return Err("Coordinate outside of the board".to_string());
+            }
+            return Ok((b'a' + x) as char);
+        }
+
+        pub fn get_number_from_coordinate(y: BoardPosType) -> Result<char, String> {
+            if y > 7 {
+                return Err("Coordinate outside of the board".to_string());
+            }
+            return Ok((b'8' - y) as char);
+        }
     }
 
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct BoardMove {
         pub from_pos: BoardPosition,
         pub to_pos: BoardPosition
@@ -84,10 +165,63 @@ pub mod chess_game {
                 to_pos: BoardPosition::new(to_x, to_y),
             }
         }
+
+        // Parse a move in UCI coordinate notation, ex "e2e4"
+        pub fn from_uci(text: &str) -> Result<Self, String> {
+            let char_vec: Vec<char> = text.chars().collect();
+            if char_vec.len() != 4 {
+                return Err("Invalid UCI move size".to_string());
+            }
+            return Ok(Self::new(
+                BoardPosition::get_coordinate_from_letter(char_vec[0])?,
+                BoardPosition::get_coordinate_from_number(char_vec[1])?,
+                BoardPosition::get_coordinate_from_letter(char_vec[2])?,
+                BoardPosition::get_coordinate_from_number(char_vec[3])?));
+        }
+
+        pub fn to_uci(&self) -> String {
+            return self.from_pos.to_algebraic_notation() + self.to_pos.to_algebraic_notation().as_str();
+        }
+    }
+
+    // A board move together with the piece a pawn is promoted to, if any
+    #[derive(Clone, Copy, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct Move {
+        pub board_move: BoardMove,
+        pub promote_piece: Option<ChessPieceId>,
+    }
+
+    impl Move {
+        pub fn new(board_move: BoardMove, promote_piece: Option<ChessPieceId>) -> Self {
+            Self {
+                board_move,
+                promote_piece
+            }
+        }
+
+        // Parse a move in UCI coordinate notation, ex "e2e4" or "e7e8q"
+        pub fn from_uci(text: &str) -> Result<Self, String> {
+            let char_vec: Vec<char> = text.chars().collect();
+            if char_vec.len() == 5 {
+                let board_move = BoardMove::from_uci(char_vec[..4].iter().collect::<String>().as_str())?;
+                return Ok(Self::new(board_move, Some(ChessPieceId::from_uci_letter(char_vec[4])?)));
+            }
+            return Ok(Self::new(BoardMove::from_uci(text)?, None));
+        }
+
+        pub fn to_uci(&self) -> String {
+            let mut text = self.board_move.to_uci();
+            if self.promote_piece.is_some() {
+                text.push(self.promote_piece.unwrap().to_uci_letter());
+            }
+            return text;
+        }
     }
 
     #[allow(dead_code)]
-    #[derive(Clone, Copy, PartialEq)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum ChessPieceId {
         Pawn,
         Knight,
@@ -101,40 +235,120 @@ pub mod chess_game {
         pub fn from_str(text: &str) -> Result<ChessPieceId, ()> {
             let mut text_copy = text.to_string();
             text_copy = text_copy.to_lowercase();
+            // English, Swedish, German and French piece names
             match text_copy.as_str() {
-                "king" => {
+                "king" | "kung" | "könig" | "roi" => {
                     return Ok(ChessPieceId::King);
                 }
-                "queen" => {
+                "queen" | "dam" | "dame" => {
                     return Ok(ChessPieceId::Queen);
                 }
-                "rook" => {
+                "rook" | "torn" | "turm" | "tour" => {
                     return Ok(ChessPieceId::Rook);
                 }
-                "pawn" => {
+                "pawn" | "bonde" | "bauer" | "pion" => {
                     return Ok(ChessPieceId::Pawn);
                 }
-                "bishop" => {
+                "bishop" | "löpare" | "läufer" | "fou" => {
                     return Ok(ChessPieceId::Bishop);
                 }
-                "knight" => {
+                "knight" | "springare" | "springer" | "cavalier" => {
                     return Ok(ChessPieceId::Knight);
                 }
                 _ => {}
             }
             return Err(());
         }
+
+        // Get piece type from the lower case letters used for promotion in UCI notation
+        pub fn from_uci_letter(letter: char) -> Result<ChessPieceId, String> {
+            match letter {
+                'q' => return Ok(ChessPieceId::Queen),
+                'r' => return Ok(ChessPieceId::Rook),
+                'b' => return Ok(ChessPieceId::Bishop),
+                'n' => return Ok(ChessPieceId::Knight),
+                _ => return Err("No matching promotion type".to_string())
+            }
+        }
+
+        pub fn to_uci_letter(&self) -> char {
+            match self {
+                ChessPieceId::Pawn => return 'p',
+                ChessPieceId::Knight => return 'n',
+                ChessPieceId::Rook => return 'r',
+                ChessPieceId::King => return 'k',
+                ChessPieceId::Queen => return 'q',
+                ChessPieceId::Bishop => return 'b',
+            }
+        }
     }
 
-    #[allow(dead_code)]
+    // The language used for piece letters when reading and writing algebraic notation
     #[derive(Clone, Copy, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub enum NotationLocale {
+        English,
+        Swedish,
+        German,
+        French,
+        Figurine, // Unicode chess glyphs, ex "♘f3"
+    }
+
+    impl NotationLocale {
+        pub fn get_piece_letter(&self, id: ChessPieceId, color: ChessPieceColor) -> char {
+            match self {
+                NotationLocale::English => match id {
+                    ChessPieceId::King => return 'K',
+                    ChessPieceId::Queen => return 'Q',
+                    ChessPieceId::Rook => return 'R',
+                    ChessPieceId::Bishop => return 'B',
+                    ChessPieceId::Knight => return 'N',
+                    ChessPieceId::Pawn => return 'P',
+                },
+                NotationLocale::Swedish | NotationLocale::German => match id {
+                    ChessPieceId::King => return 'K',
+                    ChessPieceId::Queen => return 'D',
+                    ChessPieceId::Rook => return 'T',
+                    ChessPieceId::Bishop => return 'L',
+                    ChessPieceId::Knight => return 'S',
+                    ChessPieceId::Pawn => return 'B',
+                },
+                NotationLocale::French => match id {
+                    ChessPieceId::King => return 'R',
+                    ChessPieceId::Queen => return 'D',
+                    ChessPieceId::Rook => return 'T',
+                    ChessPieceId::Bishop => return 'F',
+                    ChessPieceId::Knight => return 'C',
+                    ChessPieceId::Pawn => return 'P',
+                },
+                NotationLocale::Figurine => return ChessPiece::get_unicode_char(id, color),
+            }
+        }
+
+        pub fn get_piece_type(&self, letter: char) -> Result<ChessPieceId, String> {
+            for id in [ChessPieceId::King, ChessPieceId::Queen, ChessPieceId::Rook,
+                ChessPieceId::Bishop, ChessPieceId::Knight, ChessPieceId::Pawn] {
+                // Figurines of both colors are accepted
+                if self.get_piece_letter(id, ChessPieceColor::White) == letter
+                || self.get_piece_letter(id, ChessPieceColor::Black) == letter {
+                    return Ok(id);
+                }
+            }
+            return Err("No matching type".to_string());
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub enum ChessPieceColor {
         White,
         Black,
     }
 
     #[allow(dead_code)]
-    #[derive(Clone, Copy)]
+    #[derive(Clone, Copy, Debug)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ChessPiece {
         pub id: ChessPieceId,
         pub color: ChessPieceColor,
@@ -144,44 +358,50 @@ pub mod chess_game {
 
     impl ChessPiece {
         pub fn new(id: ChessPieceId, color: ChessPieceColor) -> Self {
-            let unicode_char;
-            match id {
-                ChessPieceId::Bishop => {
-                    unicode_char = '♝';
-                }
-                ChessPieceId::Rook => {
-                    unicode_char = '♜';
-                }
-                ChessPieceId::King => {
-                    unicode_char = '♚';
-                }
-                ChessPieceId::Queen => {
-                    unicode_char = '♛';
-                }
-                ChessPieceId::Knight => {
-                    unicode_char = '♞';
-                }
-                ChessPieceId::Pawn => {
-                    unicode_char = '♟';
-                }
-            }
             Self {
                 id: id,
                 color: color,
-                unicode_char: unicode_char,
+                unicode_char: Self::get_unicode_char(id, color),
                 moved: false,
             }
         }
+
+        // Get the unicode chess glyph of a piece, ex '♔' for a white king and '♚' for a black king
+        pub fn get_unicode_char(id: ChessPieceId, color: ChessPieceColor) -> char {
+            match color {
+                ChessPieceColor::White => match id {
+                    ChessPieceId::Bishop => return '♗',
+                    ChessPieceId::Rook => return '♖',
+                    ChessPieceId::King => return '♔',
+                    ChessPieceId::Queen => return '♕',
+                    ChessPieceId::Knight => return '♘',
+                    ChessPieceId::Pawn => return '♙',
+                },
+                ChessPieceColor::Black => match id {
+                    ChessPieceId::Bishop => return '♝',
+                    ChessPieceId::Rook => return '♜',
+                    ChessPieceId::King => return '♚',
+                    ChessPieceId::Queen => return '♛',
+                    ChessPieceId::Knight => return '♞',
+                    ChessPieceId::Pawn => return '♟',
+                },
+            }
+        }
     }
 
     #[allow(dead_code)]
-    #[derive(Clone, Copy)]
+    #[derive(Clone)]
     pub struct Game {
         board: [Option<ChessPiece>; 8 * 8],
         pub turn: ChessPieceColor,
+        pub notation_locale: NotationLocale, // Piece letters used in algebraic notation
         last_move: Option<BoardMove>,
         last_move_passant: bool,
         move_count_left: i64, // To make sure player does not play more than 50 moves
+        fullmove_number: u32, // Starts at 1 and increases after each move by black
+        castling_rights: CastlingRights,
+        history: Arc<history::MoveHistory>, // Shared between copies of the game until one of them changes it
+        flagged: Option<ChessPieceColor>, // The player who has run out of time
     }
 
     #[allow(dead_code)]
@@ -191,9 +411,14 @@ pub mod chess_game {
             Self {
                 board: [INIT; 8 * 8],
                 turn: ChessPieceColor::White,
+                notation_locale: NotationLocale::English,
                 last_move: None,
                 last_move_passant: false,
-                move_count_left: 100 // Since it is 50 moves per player
+                move_count_left: 100, // Since it is 50 moves per player
+                fullmove_number: 1,
+                castling_rights: CastlingRights::none(),
+                history: Default::default(),
+                flagged: None,
             }
         }
 
@@ -222,15 +447,46 @@ pub mod chess_game {
             for x in 0..8 {
                 self.set_pos(BoardPosition::new(x, 6), ChessPieceId::Pawn, ChessPieceColor::White);
             }
+            self.castling_rights = CastlingRights::all();
         }
 
         // Move a piece using algebraic notation
         pub fn algebraic_notation_move(&mut self, text: String) -> Result<Option<BoardMove>, String> {
+            let chess_move = self.parse_san(text.as_str())?;
+            self.move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+            return Ok(None)
+        }
+
+        // Parse a move in UCI notation, or else in algebraic notation, without doing the move
+        pub fn parse_move(&self, text: &str) -> Result<Move, String> {
+            return Move::from_uci(text).or_else(|_| self.parse_san(text));
+        }
+
+        // Parse a move in algebraic notation, without doing the move
+        pub fn parse_san(&self, text: &str) -> Result<Move, String> {
+            // Remove check, check mate, move quality and en passant annotations
+            let mut text = text.trim();
+            loop {
+                let trimmed = text.trim_end_matches(&['+', '#', '!', '?', ' '][..]);
+                let trimmed = trimmed.strip_suffix("e.p.").unwrap_or(trimmed);
+                if trimmed.len() == text.len() {
+                    break;
+                }
+                text = trimmed;
+            }
+
             // Make sure input is not too long
-            if text.len() < 2 || text.len() > 10 {
+            if text.chars().count() < 2 || text.chars().count() > 10 {
                 return Err("Invalid input size".to_string())
             }
 
+            // See if player is trying to castle
+            match text {
+                "O-O" | "0-0" => return self.parse_castle(1),
+                "O-O-O" | "0-0-0" => return self.parse_castle(-1),
+                _ => {}
+            }
+
             // Remove unnecesary letters
             let text = text.replace(&['(', ')', ',', '\"', '.', ';', 'X', 'x', ':', '='][..], "");
 
@@ -250,7 +506,13 @@ pub mod chess_game {
             }
 
             // See if player is trying to promote piece
-            let promote_piece_result = self.get_piece_type_from_letter(char_vec[char_vec.len() - 1]);
+            let mut promote_piece_result = self.get_piece_type_from_letter(char_vec[char_vec.len() - 1]);
+            if char_vec.len() == 5
+            && BoardPosition::get_coordinate_from_letter(char_vec[0]).is_ok()
+            && BoardPosition::get_coordinate_from_number(char_vec[1]).is_ok() {
+                // Coordinate notation uses lower case promotion letters, ex "e7e8q", upper case letters are still accepted
+                promote_piece_result = ChessPieceId::from_uci_letter(char_vec[4]).or(promote_piece_result);
+            }
             if promote_piece_result.is_ok() {
                 promote_piece = Some(promote_piece_result.unwrap());
                 char_vec.resize(char_vec.len() - 1, ' ');
@@ -336,7 +598,7 @@ pub mod chess_game {
                             // Make a copy of the board and try to move there
                             let mut board_copy = self.clone();
                             let test_move = BoardMove::new(from_x, from_y, to_x_input.unwrap(), to_y_input.unwrap());
-                            if board_copy.move_piece(test_move, true, promote_piece).is_ok() {
+                            if board_copy.try_move_piece(test_move, true, promote_piece).is_ok() {
                                 // Make sure there are not multiple pieces that can do that move
                                 if board_move.is_some() {
                                     return Err("Unclear which piece is to move".to_string());
@@ -355,69 +617,187 @@ pub mod chess_game {
             if !self.will_require_promotion(board_move.unwrap()) && promote_piece.is_some() {
                 return Err("Cannot promote piece".to_string());
             }
-            self.move_piece(board_move.unwrap(), true, promote_piece)?;
-            return Ok(None)
+            // Make sure the move can be done, without changing the board
+            let mut board_copy = self.clone();
+            board_copy.try_move_piece(board_move.unwrap(), true, promote_piece)?;
+            return Ok(Move::new(board_move.unwrap(), promote_piece));
         }
 
-        // Set all postitions on the board to none
-        pub fn empty_board(&mut self) {
-            for i in 0..self.board.len() {
-                self.board[i] = None;
+        // Parse a move in UCI notation without doing the move
+        pub fn parse_uci(&self, text: &str) -> Result<Move, String> {
+            let chess_move = Move::from_uci(text.trim())?;
+            self.inside_board(chess_move.board_move.from_pos)?;
+            self.inside_board(chess_move.board_move.to_pos)?;
+            if chess_move.promote_piece.is_some() && !self.will_require_promotion(chess_move.board_move) {
+                return Err("Cannot promote piece".to_string());
             }
+            let mut board_copy = self.clone();
+            board_copy.try_move_piece(chess_move.board_move, true, chess_move.promote_piece)?;
+            return Ok(chess_move);
         }
 
-        pub fn print_board(&mut self) {
-            self.print_board_with_possible_moves(None);
-        }
-
-        pub fn print_board_with_possible_moves(&mut self, possible_moves_from_pos: Option<BoardPosition>) {
-            println!("  a b c d e f g h");
-            for y in 0..8 {
-                print!("{} ", 8-y);
-                for x in 0..8 {
-                    let board_move;
-                    if possible_moves_from_pos.is_some() {
-                        board_move = BoardMove::new(possible_moves_from_pos.unwrap().x, possible_moves_from_pos.unwrap().y, x, y);
+        // Find the castling move for the current player, direction is 1 for king side and -1 for queen side
+        fn parse_castle(&self, direction: i32) -> Result<Move, String> {
+            for x in 0..8 {
+                for y in 0..8 {
+                    let piece = self.get_board_piece_clone(BoardPosition::new(x, y));
+                    if piece.is_none() || piece.unwrap().id != ChessPieceId::King || piece.unwrap().color != self.turn {
+                        continue;
                     }
-                    else {
-                        board_move = BoardMove::new(0, 0, 0, 0);
+                    let to_x = x as i32 + 2 * direction;
+                    if !(0..8).contains(&to_x) {
+                        return Err("Cannot castle outside of the board".to_string());
                     }
-
+                    let board_move = BoardMove::new(x, y, to_x as BoardPosType, y);
                     let mut board_copy = self.clone();
-                    let background_color;
-                    if possible_moves_from_pos.is_some() && board_copy.move_piece(board_move, true, Some(ChessPieceId::Queen)).is_ok() {
-                        // Color square red if piece can move there
-                        background_color = ColorTerminal::Red;
-                    }
-                    else if possible_moves_from_pos.is_some() && board_move.from_pos.x == x && board_move.from_pos.y == y {
-                        background_color = ColorTerminal::Green;
+                    board_copy.try_move_piece(board_move, true, None)?;
+                    return Ok(Move::new(board_move, None));
+                }
+            }
+            return Err("No king to castle with".to_string());
+        }
+
+        // Get the algebraic notation of a move, ex "Nbd7+" or "O-O-O#"
+        pub fn to_san(&self, board_move: &BoardMove) -> String {
+            return self.move_to_san(&Move::new(*board_move, None));
+        }
+
+        // Get the algebraic notation of a move, pawns promote to queen if no promote piece is given
+        pub fn move_to_san(&self, chess_move: &Move) -> String {
+            let board_move = chess_move.board_move;
+            let from_piece = self.get_board_piece_clone(board_move.from_pos);
+            if from_piece.is_none() {
+                return String::new();
+            }
+            let from_piece = from_piece.unwrap();
+            let promote_piece = chess_move.promote_piece.unwrap_or(ChessPieceId::Queen);
+            let d_x = board_move.to_pos.x as i32 - board_move.from_pos.x as i32;
+            let d_y = board_move.to_pos.y as i32 - board_move.from_pos.y as i32;
+            let to_piece = self.get_board_piece_clone(board_move.to_pos);
+            let mut en_passant = false;
+
+            let mut text = String::new();
+            if from_piece.id == ChessPieceId::King && d_x.abs() == 2 && d_y == 0 {
+                // Castling
+                if d_x > 0 {
+                    text += "O-O";
+                }
+                else {
+                    text += "O-O-O";
+                }
+            }
+            else if from_piece.id == ChessPieceId::Pawn {
+                if d_x != 0 {
+                    // Pawns always capture diagonally, the square is empty on en passant
+                    en_passant = to_piece.is_none();
+                    text.push(BoardPosition::get_letter_from_coordinate(board_move.from_pos.x).unwrap_or('?'));
+                    text.push('x');
+                }
+                text += board_move.to_pos.to_algebraic_notation().as_str();
+                if self.will_require_promotion(board_move) {
+                    text.push('=');
+                    text.push(self.get_letter_from_piece_type(promote_piece, from_piece.color));
+                }
+            }
+            else {
+                text.push(self.get_letter_from_piece_type(from_piece.id, from_piece.color));
+                text += self.get_disambiguation(board_move, from_piece).as_str();
+                if to_piece.is_some() {
+                    text.push('x');
+                }
+                text += board_move.to_pos.to_algebraic_notation().as_str();
+            }
+
+            // See if the move gives check or check mate
+            let mut board_copy = self.clone();
+            if board_copy.try_move_piece(board_move, true, Some(promote_piece)).is_ok() {
+                if board_copy.is_check_mate() {
+                    text.push('#');
+                }
+                else if board_copy.is_check().is_some() {
+                    text.push('+');
+                }
+            }
+            if en_passant {
+                text += " e.p.";
+            }
+            return text;
+        }
+
+        // Get the file and/or rank needed to tell which piece is moving, if several pieces can do the move
+        fn get_disambiguation(&self, board_move: BoardMove, from_piece: ChessPiece) -> String {
+            let mut ambiguous = false;
+            let mut same_file = false;
+            let mut same_rank = false;
+            for x in 0..8 {
+                for y in 0..8 {
+                    if x == board_move.from_pos.x && y == board_move.from_pos.y {
+                        continue;
                     }
-                    else if (x + y) % 2 == 0 {
-                        background_color = ColorTerminal::LightBlue;
+                    let piece = self.get_board_piece_clone(BoardPosition::new(x, y));
+                    if piece.is_none() || piece.unwrap().id != from_piece.id || piece.unwrap().color != from_piece.color {
+                        continue;
                     }
-                    else {
-                        background_color = ColorTerminal::Blue;
+                    let mut board_copy = self.clone();
+                    let other_move = BoardMove::new(x, y, board_move.to_pos.x, board_move.to_pos.y);
+                    if board_copy.try_move_piece(other_move, true, Some(ChessPieceId::Queen)).is_ok() {
+                        ambiguous = true;
+                        same_file = same_file || x == board_move.from_pos.x;
+                        same_rank = same_rank || y == board_move.from_pos.y;
                     }
-                    // Get a reference to a position on the board
-                    let board_ref = *self.get_board_ref(BoardPosition::new(x, y)).unwrap();
-                    if board_ref.is_none() {
-                        // If there is nothing there, just print two spaces
-                        print_color("  ", ColorTerminal::White, background_color);
-                    } else {
-                        if board_ref.as_ref().unwrap().color == ChessPieceColor::Black {
-                            print_color(board_ref.as_ref().unwrap().unicode_char.to_string().as_str(), ColorTerminal::Black, background_color);
-                            print_color(" ", ColorTerminal::Black, background_color);
-                        }
-                        else {
-                            print_color(board_ref.as_ref().unwrap().unicode_char.to_string().as_str(), ColorTerminal::White, background_color);
-                            print_color(" ", ColorTerminal::White, background_color);
-                        }
+                }
+            }
+
+            let mut text = String::new();
+            if !ambiguous {
+                return text;
+            }
+            let file = BoardPosition::get_letter_from_coordinate(board_move.from_pos.x).unwrap_or('?');
+            let rank = BoardPosition::get_number_from_coordinate(board_move.from_pos.y).unwrap_or('?');
+            if !same_file {
+                text.push(file);
+            }
+            else if !same_rank {
+                text.push(rank);
+            }
+            else {
+                text.push(file);
+                text.push(rank);
+            }
+            return text;
+        }
+
+        // Set all postitions on the board to none
+        pub fn empty_board(&mut self) {
+            for i in 0..self.board.len() {
+                self.board[i] = None;
+            }
+            self.clear_history();
+            self.flagged = None;
+        }
+
+        // Print the board with the side of perspective at the bottom
+        #[cfg(feature = "terminal")]
+        pub fn print_board(&self, perspective: ChessPieceColor) {
+            self.print_board_with_possible_moves(None, perspective);
+        }
+
+        #[cfg(feature = "terminal")]
+        pub fn print_board_with_possible_moves(&self, possible_moves_from_pos: Option<BoardPosition>, perspective: ChessPieceColor) {
+            let mut options = RenderOptions {
+                orientation: perspective,
+                ..Default::default()
+            };
+            if possible_moves_from_pos.is_some() {
+                let from_pos = possible_moves_from_pos.unwrap();
+                options.selected = Some(from_pos);
+                for board_move in self.get_possible_moves() {
+                    if board_move.from_pos == from_pos {
+                        options.highlights.push(board_move.to_pos);
                     }
                 }
-                print!("{} ", 8-y);
-                println!();
             }
-            println!("  a b c d e f g h");
+            print!("{}", AnsiRenderer.render_to_string(self, &options));
         }
 
         // Get a reference to a coordinate on the board
@@ -428,9 +808,55 @@ pub mod chess_game {
             return Ok(&mut self.board[(pos.x + 8 * pos.y) as usize]);
         }
 
+        // Get a read only reference to a coordinate on the board
+        fn get_board_piece_ref(&self, pos: BoardPosition) -> Result<&Option<ChessPiece>, String> {
+            if self.inside_board(pos).is_err() {
+                return Err("Cannot acces pieces outside board".to_string());
+            }
+            return Ok(&self.board[(pos.x + 8 * pos.y) as usize]);
+        }
+
+        // Get the piece on a position, or None if the square is empty or outside the board
+        pub fn piece_at(&self, pos: BoardPosition) -> Option<ChessPiece> {
+            if self.inside_board(pos).is_err() {
+                return None;
+            }
+            return self.board[(pos.x + 8 * pos.y) as usize];
+        }
+
+        // Get the color of the player whose turn it is
+        pub fn turn(&self) -> ChessPieceColor {
+            return self.turn;
+        }
+
+        // Get the number of half moves since the last capture or pawn move
+        pub fn halfmove_clock(&self) -> u32 {
+            return (100 - self.move_count_left).max(0) as u32;
+        }
+
+        // Get the number of the current move, starting at 1 and increasing after black moves
+        pub fn fullmove_number(&self) -> u32 {
+            return self.fullmove_number;
+        }
+
+        // Get which castling moves are still allowed
+        pub fn castling_rights(&self) -> CastlingRights {
+            return self.castling_rights;
+        }
+
+        // Set which castling moves are allowed, ex when editing a position
+        pub fn set_castling_rights(&mut self, castling_rights: CastlingRights) {
+            self.castling_rights = castling_rights;
+        }
+
+        // Get the last move that was done, if any
+        pub fn last_move(&self) -> Option<BoardMove> {
+            return self.last_move;
+        }
+
         // Get a clone of a position on the board
-        pub fn get_board_piece_clone(&mut self, pos: BoardPosition) -> Option<ChessPiece> {
-            return (*self.get_board_ref(pos).unwrap()).clone();
+        pub fn get_board_piece_clone(&self, pos: BoardPosition) -> Option<ChessPiece> {
+            return (*self.get_board_piece_ref(pos).unwrap()).clone();
         }
 
         // Set a position on the board
@@ -451,15 +877,26 @@ pub mod chess_game {
         }
 
         // Returns true if the game is over
-        pub fn game_is_over(&mut self) -> bool {
-            if self.is_check_mate() || self.is_stale_mate() || self.max_move_count_reached() {
+        pub fn game_is_over(&self) -> bool {
+            if self.is_check_mate() || self.is_stale_mate() || self.max_move_count_reached() || self.flagged.is_some() {
                 return true;
             }
             return false;
         }
 
         // Returns if there is a winner, and what color it is
-        pub fn get_winner(&mut self) -> Option<ChessPieceColor> {
+        pub fn get_winner(&self) -> Option<ChessPieceColor> {
+            if self.flagged.is_some() {
+                // Running out of time is a draw if the opponent could never checkmate
+                let winner = match self.flagged.unwrap() {
+                    ChessPieceColor::White => ChessPieceColor::Black,
+                    ChessPieceColor::Black => ChessPieceColor::White,
+                };
+                if self.can_checkmate(winner) {
+                    return Some(winner);
+                }
+                return None;
+            }
             if self.is_check_mate() {
                 if self.turn == ChessPieceColor::White {
                     return Some(ChessPieceColor::Black);
@@ -473,7 +910,7 @@ pub mod chess_game {
             }
         }
 
-        pub fn is_check(&mut self) -> Option<BoardMove> {
+        pub fn is_check(&self) -> Option<BoardMove> {
             for x in 0..8 {
                 for y in 0..8 {
                     let piece = self.get_board_piece_clone(BoardPosition::new(x, y));
@@ -489,7 +926,7 @@ pub mod chess_game {
 
                                 // Checks if the move captures the king
                                 // If it takes the king, you dont have to think about check
-                                if board_copy.move_piece(board_move, false, Some(ChessPieceId::Queen)).is_ok() {
+                                if board_copy.try_move_piece(board_move, false, Some(ChessPieceId::Queen)).is_ok() {
                                     return Some(board_move);
                                 }
                             }
@@ -500,7 +937,7 @@ pub mod chess_game {
             return None;
         }
 
-        pub fn is_check_mate(&mut self) -> bool{
+        pub fn is_check_mate(&self) -> bool{
             if self.is_check().is_some() && self.get_possible_moves().is_empty() {
                 return true;
             }
@@ -509,7 +946,7 @@ pub mod chess_game {
             }
         }
 
-        pub fn is_stale_mate(&mut self) -> bool{
+        pub fn is_stale_mate(&self) -> bool{
             if self.is_check().is_none() && self.get_possible_moves().is_empty() {
                 return true;
             }
@@ -518,11 +955,11 @@ pub mod chess_game {
             }
         }
 
-        pub fn get_possible_moves(&mut self) -> LinkedList<BoardMove> {
+        pub fn get_possible_moves(&self) -> LinkedList<BoardMove> {
             let mut board_moves:LinkedList<BoardMove> = Default::default();
             for x in 0..8 {
                 for y in 0..8 {
-                    let piece = (self.get_board_ref(BoardPosition::new(x, y)).unwrap()).clone();
+                    let piece = (self.get_board_piece_ref(BoardPosition::new(x, y)).unwrap()).clone();
                     // If it is a king
                     if piece.is_some() && piece.unwrap().color == self.turn
                     {
@@ -531,7 +968,7 @@ pub mod chess_game {
                                 // Iterate spaces to see if it can move there
                                 let board_move = BoardMove::new(x, y, x2, y2);
                                 let mut board_copy = self.clone();
-                                if board_copy.move_piece(board_move, true, Some(ChessPieceId::Queen)).is_ok() {
+                                if board_copy.try_move_piece(board_move, true, Some(ChessPieceId::Queen)).is_ok() {
                                     board_moves.push_back(board_move);
                                 }
                             }
@@ -542,7 +979,41 @@ pub mod chess_game {
             return board_moves;
         }
 
+        // Get all legal moves with one move for each promotion piece, sorted by their binary encoding
+        pub fn legal_moves(&self) -> Vec<Move> {
+            let mut moves: Vec<Move> = Vec::new();
+            for board_move in self.get_possible_moves() {
+                if self.will_require_promotion(board_move) {
+                    for id in [ChessPieceId::Queen, ChessPieceId::Rook, ChessPieceId::Bishop, ChessPieceId::Knight] {
+                        moves.push(Move::new(board_move, Some(id)));
+                    }
+                }
+                else {
+                    moves.push(Move::new(board_move, None));
+                }
+            }
+            moves.sort_by_key(|chess_move| chess_move.encode());
+            return moves;
+        }
+
+        // Do a move and remember it in the history of the game
         pub fn move_piece(&mut self, board_move: BoardMove, check_for_check: bool, promote_piece: Option<ChessPieceId>) -> Result<(), String> {
+            if self.flagged.is_some() {
+                return Err("The game is over, time has run out".to_string());
+            }
+            if promote_piece.is_some() && !self.will_require_promotion(board_move) {
+                // Report what is wrong with the move itself first, if anything
+                self.clone().try_move_piece(board_move, check_for_check, None)?;
+                return Err("Cannot promote piece".to_string());
+            }
+            let before_move = self.clone();
+            self.try_move_piece(board_move, check_for_check, promote_piece)?;
+            self.record_move(&before_move, Move::new(board_move, promote_piece));
+            return Ok(());
+        }
+
+        // Do a move without remembering it, used when testing moves on copies of the board
+        fn try_move_piece(&mut self, board_move: BoardMove, check_for_check: bool, promote_piece: Option<ChessPieceId>) -> Result<(), String> {
             self.is_move(board_move)?;
             self.inside_board(board_move.from_pos)?;
             self.inside_board(board_move.to_pos)?;
@@ -557,7 +1028,7 @@ pub mod chess_game {
             // Make sure move does not lead to check
             if check_for_check {
                 let mut self_copy = self.clone();
-                self_copy.move_piece(board_move, false, Some(ChessPieceId::Queen))?;
+                self_copy.try_move_piece(board_move, false, Some(ChessPieceId::Queen))?;
                 self_copy.turn = self.turn;
                 if self_copy.is_check().is_some() {
                     return Err("Move leads to check!".to_string());
@@ -674,19 +1145,15 @@ pub mod chess_game {
                 return Err("Cannot convert pawn not on other side".to_string());
             }
         }
-        pub fn get_piece_type_from_letter(&mut self, letter: char) -> Result<ChessPieceId, String> {
-            match letter {
-                'R' => return Ok(ChessPieceId::Rook),
-                'P' => return Ok(ChessPieceId::Pawn),
-                'Q' => return Ok(ChessPieceId::Queen),
-                'K' => return Ok(ChessPieceId::King),
-                'N' => return Ok(ChessPieceId::Knight),
-                'B' => return Ok(ChessPieceId::Bishop),
-                _ => return Err("No matching type".to_string())
-            }
+        pub fn get_piece_type_from_letter(&self, letter: char) -> Result<ChessPieceId, String> {
+            return self.notation_locale.get_piece_type(letter);
         }
 
-        fn is_unblocked_straight_line(&mut self, board_move: BoardMove) -> Result<(), String> {
+        pub fn get_letter_from_piece_type(&self, id: ChessPieceId, color: ChessPieceColor) -> char {
+            return self.notation_locale.get_piece_letter(id, color);
+        }
+
+        fn is_unblocked_straight_line(&self, board_move: BoardMove) -> Result<(), String> {
             // Make sure it is a straight line
             if board_move.from_pos.x == board_move.to_pos.x {
                 for y in 1..(board_move.from_pos.y as i32 - board_move.to_pos.y as i32).abs() {
@@ -695,7 +1162,7 @@ pub mod chess_game {
                         d_y = -d_y;
                     }
                     if self
-                        .get_board_ref(BoardPosition::new(board_move.from_pos.x, (d_y + board_move.from_pos.y as i32) as u8))
+                        .get_board_piece_ref(BoardPosition::new(board_move.from_pos.x, (d_y + board_move.from_pos.y as i32) as u8))
                         .unwrap()
                         .as_ref()
                         .is_some()
@@ -711,7 +1178,7 @@ pub mod chess_game {
                         d_x = -d_x;
                     }
                     if self
-                        .get_board_ref(BoardPosition::new((board_move.from_pos.x as i32 + d_x) as u8, board_move.from_pos.y))
+                        .get_board_piece_ref(BoardPosition::new((board_move.from_pos.x as i32 + d_x) as u8, board_move.from_pos.y))
                         .unwrap()
                         .as_ref()
                         .is_some()
@@ -725,13 +1192,14 @@ pub mod chess_game {
         }
 
         fn reset_move_count_left(&mut self) {
-            self.move_count_left = 100;
+            // One more than the limit, since end_turn counts the move that reset it
+            self.move_count_left = 101;
         }
-        fn max_move_count_reached(&mut self) -> bool {
+        fn max_move_count_reached(&self) -> bool {
             return self.move_count_left < 1;
         }
 
-        fn is_unblocked_diagonal_line(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn is_unblocked_diagonal_line(&self, board_move: BoardMove) -> Result<(), String> {
             // Make sure it is a diagonal
             if (board_move.from_pos.x as i32 - board_move.to_pos.x as i32).abs()
                 == (board_move.from_pos.y as i32 - board_move.to_pos.y as i32).abs()
@@ -749,7 +1217,7 @@ pub mod chess_game {
                     let pos = BoardPosition::new((d_x + board_move.from_pos.x as i32) as u8, 
                     (d_y + board_move.from_pos.y as i32) as u8);
                     if self
-                        .get_board_ref(pos)
+                        .get_board_piece_ref(pos)
                         .unwrap()
                         .as_ref()
                         .is_some()
@@ -762,14 +1230,14 @@ pub mod chess_game {
             return Err("Not a valid diagonal!".to_string());
         }
 
-        fn is_move(&mut self, board_move: BoardMove) -> Result<(), String> {
+        fn is_move(&self, board_move: BoardMove) -> Result<(), String> {
             if (board_move.from_pos.x == board_move.to_pos.x) && (board_move.from_pos.y == board_move.to_pos.y) {
                 return Err("Cannot do nothing during your turn!".to_string());
             }
             return Ok(());
         }
 
-        fn is_pieces_same_color(&mut self, pos1: BoardPosition, pos2: BoardPosition) -> bool {
+        fn is_pieces_same_color(&self, pos1: BoardPosition, pos2: BoardPosition) -> bool {
             let piece1 = self.get_board_piece_clone(pos1);
             let piece2 = self.get_board_piece_clone(pos2);
             if piece1.is_some() && piece2.is_some() && piece1.unwrap().color == piece2.unwrap().color {
@@ -780,15 +1248,15 @@ pub mod chess_game {
             }
         }
 
-        fn is_piece_id(&mut self, pos: BoardPosition, id: ChessPieceId) -> Result<(), String> {
-            if self.get_board_ref(pos)?.is_some() && self.get_board_ref(pos)?.unwrap().id == id {
+        fn is_piece_id(&self, pos: BoardPosition, id: ChessPieceId) -> Result<(), String> {
+            if self.get_board_piece_ref(pos)?.is_some() && self.get_board_piece_ref(pos)?.unwrap().id == id {
                 return Ok(());
             }
             else {
                 return Err("Piece id does not match".to_string());
             }
         }
-        fn inside_board(&mut self, pos: BoardPosition) -> Result<(), String> {
+        fn inside_board(&self, pos: BoardPosition) -> Result<(), String> {
             if pos.x > 7 || pos.y > 7 {
                 return Err("Outside of the board!".to_string());
             }
@@ -800,11 +1268,12 @@ pub mod chess_game {
                 self.turn = ChessPieceColor::Black;
             } else {
                 self.turn = ChessPieceColor::White;
+                self.fullmove_number += 1;
             }
             self.move_count_left = self.move_count_left - 1;
         }
 
-        fn will_require_promotion(&mut self, board_move: BoardMove) -> bool {
+        fn will_require_promotion(&self, board_move: BoardMove) -> bool {
             let piece = self.get_board_piece_clone(board_move.from_pos);
             if piece.is_none() {
                 return false;
@@ -837,11 +1306,32 @@ pub mod chess_game {
                 .take();
             if self.get_board_ref(board_move.to_pos).unwrap().is_some() {
                 self.get_board_ref(board_move.to_pos).unwrap().as_mut().unwrap().moved = true;
+                if self.get_board_ref(board_move.to_pos).unwrap().unwrap().id == ChessPieceId::King {
+                    let color = self.get_board_ref(board_move.to_pos).unwrap().unwrap().color;
+                    self.castling_rights.remove_color(color);
+                }
             }
+            // Castling is not allowed after the rook moves or is taken
+            self.remove_castling_right_on(board_move.from_pos);
+            self.remove_castling_right_on(board_move.to_pos);
             self.last_move_passant = false;
             self.last_move = Some(board_move);
         }
-        fn board_move_not_same_color_pieces(&mut self, board_move: BoardMove) -> Result<(), String> {
+
+        // Remove the castling right that needs a rook on a corner of the board
+        fn remove_castling_right_on(&mut self, pos: BoardPosition) {
+            let color = match pos.y {
+                7 => ChessPieceColor::White,
+                0 => ChessPieceColor::Black,
+                _ => return,
+            };
+            match pos.x {
+                7 => self.castling_rights.set(color, CastleSide::KingSide, false),
+                0 => self.castling_rights.set(color, CastleSide::QueenSide, false),
+                _ => {}
+            }
+        }
+        fn board_move_not_same_color_pieces(&self, board_move: BoardMove) -> Result<(), String> {
             if self.is_pieces_same_color(board_move.from_pos, board_move.to_pos) {
                 return Err("Cannot move piece to piece of same color".to_string())
             }
@@ -849,8 +1339,8 @@ pub mod chess_game {
                 return Ok(());
             }
         }
-        fn board_move_is_forward(&mut self, board_move: BoardMove) -> Result<(), String> {
-            let piece = self.get_board_ref(board_move.from_pos).unwrap();
+        fn board_move_is_forward(&self, board_move: BoardMove) -> Result<(), String> {
+            let piece = self.get_board_piece_ref(board_move.from_pos).unwrap();
             if piece.is_none() {
                 return Err("Piece is none".to_string());
             }
@@ -1020,9 +1510,6 @@ pub mod chess_game {
             if from_piece.is_none() || (from_piece.unwrap().id != ChessPieceId::King) {
                 return Err("Must have king to castle".to_string());
             }
-            if from_piece.unwrap().moved == true {
-                return Err("Cannot castle with moved piece".to_string());
-            }
             if (board_move.to_pos.x as i32- board_move.from_pos.x as i32).abs() != 2 {
                 return Err("Castle requires piece to move two to the side".to_string());
             }
@@ -1037,6 +1524,10 @@ pub mod chess_game {
             else {
                 direction = -1;
             }
+            let side = if direction == 1 { CastleSide::KingSide } else { CastleSide::QueenSide };
+            if !self.castling_rights.has(from_piece.unwrap().color, side) {
+                return Err("No castling rights left for that side".to_string());
+            }
             // Make sure it is not check on any of the squares king is moving on
             for i in 0..3 {
                 // Make a clone of the board
@@ -1053,27 +1544,34 @@ pub mod chess_game {
                 }
 
             }
-            for i in 1..5 {
-                let rook_x = (board_move.from_pos.x as i32+i*direction) as BoardPosType;
-                let rook_y = board_move.from_pos.y;
-                let rook_pos = BoardPosition::new(rook_x, rook_y);
-                if self.inside_board(rook_pos).is_err() {
-                    break;
-                }
-                if self.is_piece_id(BoardPosition::new(rook_x, rook_y), ChessPieceId::Rook).is_ok() {
-                    if self.get_board_ref(BoardPosition::new(rook_x, rook_y)).unwrap().unwrap().moved == true {
-                        return Err("Cannot castle with moved rook".to_string());
-                    }
-                    
-                    // Move king and rook
-                    self.force_move_piece(board_move);
-                    let rook_move = BoardMove::new(rook_x, rook_y, 
-                        (board_move.from_pos.x as i32 + direction) as BoardPosType, rook_y);
-                    self.force_move_piece(rook_move);
-                    return Ok(());
+            // The rook must be on the corner of the side castled towards, beyond where the king moves to
+            let rook_x: BoardPosType = if direction == 1 { 7 } else { 0 };
+            let rook_y = board_move.from_pos.y;
+            let rook = self.get_board_piece_clone(BoardPosition::new(rook_x, rook_y));
+            if (rook_x as i32 - board_move.to_pos.x as i32) * direction <= 0
+                || rook.is_none()
+                || rook.unwrap().id != ChessPieceId::Rook
+                || rook.unwrap().color != from_piece.unwrap().color
+            {
+                return Err("No rook to move with".to_string());
+            }
+            // Make sure there are no pieces between the king and the rook
+            let mut x = board_move.from_pos.x as i32 + direction;
+            while x != rook_x as i32 {
+                if self.get_board_piece_clone(BoardPosition::new(x as BoardPosType, rook_y)).is_some() {
+                    return Err("Path to rook is blocked".to_string());
                 }
+                x += direction;
             }
-            return Err("No rook to move with".to_string());
+
+            // Move king and rook
+            self.force_move_piece(board_move);
+            let rook_move = BoardMove::new(rook_x, rook_y,
+                (board_move.from_pos.x as i32 + direction) as BoardPosType, rook_y);
+            self.force_move_piece(rook_move);
+            // The last move is the one the king did, not the rook
+            self.last_move = Some(board_move);
+            return Ok(());
         }
 
         fn bishop_move(&mut self, board_move: BoardMove) -> Result<(), String> {
@@ -0,0 +1,94 @@
+/* Built and run by ffi_test in tests/test.rs, exits with 0 when every check passes */
+#include <stdio.h>
+#include <string.h>
+
+#include "chess_engine.h"
+
+static int failures = 0;
+
+#define CHECK(condition)                                              \
+    do {                                                              \
+        if (!(condition)) {                                           \
+            printf("%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            failures++;                                               \
+        }                                                             \
+    } while (0)
+
+static int has_move(const char *moves, size_t count, const char *uci) {
+    for (size_t i = 0; i < count; i++) {
+        if (strcmp(moves + i * CHESS_UCI_MOVE_SIZE, uci) == 0) {
+            return 1;
+        }
+    }
+    return 0;
+}
+
+int main(void) {
+    char moves[CHESS_MAX_LEGAL_MOVES * CHESS_UCI_MOVE_SIZE];
+    char fen[CHESS_FEN_BUFFER_SIZE];
+    size_t count = 0;
+
+    ChessGame *game = chess_game_new();
+    CHECK(game != NULL);
+
+    CHECK(chess_game_legal_moves(game, moves, CHESS_MAX_LEGAL_MOVES, &count) == CHESS_OK);
+    CHECK(count == 20);
+    CHECK(has_move(moves, count, "e2e4"));
+    CHECK(has_move(moves, count, "g1f3"));
+    CHECK(chess_game_legal_moves(game, moves, 5, &count) == CHESS_ERROR_BUFFER_TOO_SMALL);
+    CHECK(count == 20);
+
+    /* Rule errors */
+    CHECK(chess_game_make_move(game, "e2") == CHESS_ERROR_INVALID_MOVE_TEXT);
+    CHECK(chess_game_make_move(game, "e3e4") == CHESS_ERROR_NO_PIECE);
+    CHECK(strcmp(chess_game_last_error(game), "No piece on square selected!") == 0);
+    CHECK(chess_game_make_move(game, "e7e5") == CHESS_ERROR_NOT_YOUR_PIECE);
+    CHECK(chess_game_make_move(game, "d1d2") == CHESS_ERROR_OWN_PIECE_ON_TARGET);
+    CHECK(chess_game_make_move(game, "e2e5") == CHESS_ERROR_ILLEGAL_MOVE);
+    CHECK(chess_game_make_move(game, "e2e4q") == CHESS_ERROR_PROMOTION);
+    CHECK(chess_game_make_move(NULL, "e2e4") == CHESS_ERROR_NULL_POINTER);
+    CHECK(chess_game_make_move(game, "\xff\xfe") == CHESS_ERROR_INVALID_UTF8);
+
+    /* Fool's mate */
+    CHECK(chess_game_make_move(game, "f2f3") == CHESS_OK);
+    CHECK(strcmp(chess_game_last_error(game), "") == 0);
+    CHECK(chess_game_make_move(game, "e7e5") == CHESS_OK);
+    CHECK(chess_game_make_move(game, "g2g4") == CHESS_OK);
+    CHECK(chess_game_outcome(game) == CHESS_OUTCOME_ONGOING);
+    CHECK(chess_game_make_move(game, "d8h4") == CHESS_OK);
+    CHECK(chess_game_is_check(game) == 1);
+    CHECK(chess_game_outcome(game) == CHESS_OUTCOME_BLACK_WINS);
+    CHECK(chess_game_legal_moves(game, moves, CHESS_MAX_LEGAL_MOVES, &count) == CHESS_OK);
+    CHECK(count == 0);
+    CHECK(chess_game_make_move(game, "a2a3") == CHESS_ERROR_GAME_OVER);
+
+    /* Positions from FEN */
+    CHECK(chess_game_load_fen(game, "not a fen") == CHESS_ERROR_INVALID_FEN);
+    CHECK(chess_game_outcome(game) == CHESS_OUTCOME_BLACK_WINS);
+    CHECK(chess_game_load_fen(game, "4k3/8/8/8/8/8/4r3/4K3 w - - 0 1") == CHESS_OK);
+    CHECK(chess_game_make_move(game, "e1d2") == CHESS_ERROR_LEAVES_KING_IN_CHECK);
+    CHECK(chess_game_make_move(game, "e1e2") == CHESS_OK);
+    CHECK(chess_game_fen(game, fen, sizeof(fen)) == CHESS_OK);
+    CHECK(strcmp(fen, "4k3/8/8/8/8/8/4K3/8 b - - 0 1") == 0);
+    CHECK(chess_game_fen(game, fen, 4) == CHESS_ERROR_BUFFER_TOO_SMALL);
+    CHECK(chess_game_outcome(game) == CHESS_OUTCOME_ONGOING);
+
+    /* Stalemate */
+    CHECK(chess_game_load_fen(game, "7k/5K2/6Q1/8/8/8/8/8 b - - 0 1") == CHESS_OK);
+    CHECK(chess_game_is_check(game) == 0);
+    CHECK(chess_game_outcome(game) == CHESS_OUTCOME_DRAW);
+
+    CHECK(chess_game_load_fen(game, "4k3/P7/8/8/8/8/8/4K3 w - - 0 1") == CHESS_OK);
+    CHECK(chess_game_make_move(game, "a7a8") == CHESS_ERROR_PROMOTION);
+    CHECK(chess_game_make_move(game, "a7a8q") == CHESS_OK);
+
+    chess_game_free(game);
+    chess_game_free(NULL);
+
+    if (failures != 0) {
+        printf("%d checks failed\n", failures);
+        return 1;
+    }
+    printf("All checks passed\n");
+    return 0;
+}
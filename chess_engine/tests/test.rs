@@ -326,6 +326,7 @@ mod chess_lib_test {
         // Make sure you can castle to the left
         let mut game = Game::new();
         game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
         game.set_pos(BoardPosition::new(0, 0), ChessPieceId::Rook, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(3, 0), ChessPieceId::King, ChessPieceColor::White);
         let board_move = BoardMove::new(3, 0, 1, 0);
@@ -339,6 +340,7 @@ mod chess_lib_test {
         // Make sure you can castle to the right
         let mut game = Game::new();
         game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
         game.set_pos(BoardPosition::new(7, 0), ChessPieceId::Rook, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(3, 0), ChessPieceId::King, ChessPieceColor::White);
         let board_move = BoardMove::new(3, 0, 5, 0);
@@ -352,10 +354,13 @@ mod chess_lib_test {
         // Make sure you cannot castle if king has moved
         let mut game = Game::new();
         game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
         game.set_pos(BoardPosition::new(0, 0), ChessPieceId::Rook, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(3, 0), ChessPieceId::King, ChessPieceColor::White);
-        let board_ref = game.get_board_ref(BoardPosition::new(3, 0)).unwrap();
-        board_ref.as_mut().unwrap().moved = true;
+        assert_eq!(game.move_piece(BoardMove::new(3, 0, 3, 1), false, None).is_ok(), true);
+        game.turn = ChessPieceColor::White;
+        assert_eq!(game.move_piece(BoardMove::new(3, 1, 3, 0), false, None).is_ok(), true);
+        game.turn = ChessPieceColor::White;
         let board_move = BoardMove::new(3, 0, 1, 0);
         assert_eq!(game.move_piece(board_move, false, None).is_ok(), false);
         assert_eq!(game.get_board_piece_clone(BoardPosition::new(0, 0)).is_some(), true);
@@ -364,19 +369,22 @@ mod chess_lib_test {
         // Make sure you cannot castle if rook has moved
         let mut game = Game::new();
         game.empty_board();
-        game.set_pos(BoardPosition::new(0, 0), ChessPieceId::Rook, ChessPieceColor::White);
-        game.set_pos(BoardPosition::new(3, 0), ChessPieceId::King, ChessPieceColor::White);
-        let board_ref = game.get_board_ref(BoardPosition::new(0, 0)).unwrap();
-        board_ref.as_mut().unwrap().moved = true;
-        //game.get_board_ref(BoardPosition::new(0, 0)).unwrap().as_mut().unwrap().moved = true;
-        let board_move = BoardMove::new(3, 0, 1, 0);
+        game.set_castling_rights(CastlingRights::all());
+        game.set_pos(BoardPosition::new(0, 7), ChessPieceId::Rook, ChessPieceColor::White);
+        game.set_pos(BoardPosition::new(3, 7), ChessPieceId::King, ChessPieceColor::White);
+        assert_eq!(game.move_piece(BoardMove::new(0, 7, 0, 6), false, None).is_ok(), true);
+        game.turn = ChessPieceColor::White;
+        assert_eq!(game.move_piece(BoardMove::new(0, 6, 0, 7), false, None).is_ok(), true);
+        game.turn = ChessPieceColor::White;
+        let board_move = BoardMove::new(3, 7, 1, 7);
         assert_eq!(game.move_piece(board_move, false, None).is_ok(), false);
-        assert_eq!(game.get_board_piece_clone(BoardPosition::new(0, 0)).is_some(), true);
-        assert_eq!(game.get_board_piece_clone(BoardPosition::new(3, 0)).is_some(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::new(0, 7)).is_some(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::new(3, 7)).is_some(), true);
 
         // Make sure you cannot castle when one of the squares is in check
         let mut game = Game::new();
         game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
         game.set_pos(BoardPosition::new(7, 0), ChessPieceId::Rook, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(3, 0), ChessPieceId::King, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(4, 2), ChessPieceId::Rook, ChessPieceColor::Black);
@@ -388,6 +396,7 @@ mod chess_lib_test {
         // Make sure you can castle when the rook is threatened
         let mut game = Game::new();
         game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
         game.set_pos(BoardPosition::new(7, 0), ChessPieceId::Rook, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(3, 0), ChessPieceId::King, ChessPieceColor::White);
         game.set_pos(BoardPosition::new(7, 2), ChessPieceId::Rook, ChessPieceColor::Black);
@@ -608,4 +617,1371 @@ mod chess_lib_test {
         game.set_up_board();
         assert_eq!(game.algebraic_notation_move("a2 a3".to_string()).is_ok(), false);
     }
+
+    #[test]
+    fn san_test() {
+        // Make sure a simple knight move is written correctly
+        let mut game = Game::new();
+        game.set_up_board();
+        let from_pos = BoardPosition::from_algebraic_notation("g1").unwrap();
+        let to_pos = BoardPosition::from_algebraic_notation("f3").unwrap();
+        assert_eq!(game.to_san(&BoardMove { from_pos, to_pos }), "Nf3");
+        assert_eq!(game.to_san(&BoardMove::new(4, 6, 4, 4)), "e4");
+
+        // Make sure the moving piece is specified when two pieces can do the move
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("a1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("e5").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        assert_eq!(game.to_san(&BoardMove::new(0, 7, 1, 7)), "Rab1");
+        assert_eq!(game.to_san(&BoardMove::new(4, 7, 4, 5)), "R1e3");
+
+        // Make sure check mate is marked
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.algebraic_notation_move("f3".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("e5".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("g4".to_string()).is_ok(), true);
+        assert_eq!(game.to_san(&BoardMove::new(3, 0, 7, 4)), "Qh4#");
+        assert_eq!(game.to_san(&BoardMove::new(5, 0, 1, 4)), "Bb4");
+
+        // Make sure check and captures are marked
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("d1").unwrap(), ChessPieceId::Queen, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("d7").unwrap(), ChessPieceId::Knight, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("e8").unwrap(), ChessPieceId::King, ChessPieceColor::Black);
+        assert_eq!(game.to_san(&BoardMove::new(3, 7, 3, 1)), "Qxd7+");
+
+        // Make sure castling is written correctly
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("a1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("h1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        assert_eq!(game.to_san(&BoardMove::new(4, 7, 6, 7)), "O-O");
+        assert_eq!(game.to_san(&BoardMove::new(4, 7, 2, 7)), "O-O-O");
+
+        // Make sure en passant and promotion are written correctly
+        let mut game = Game::new();
+        game.empty_board();
+        game.turn = ChessPieceColor::Black;
+        game.set_pos(BoardPosition::from_algebraic_notation("d7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("e5").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("b7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("c8").unwrap(), ChessPieceId::Rook, ChessPieceColor::Black);
+        assert_eq!(game.algebraic_notation_move("d5".to_string()).is_ok(), true);
+        assert_eq!(game.to_san(&BoardMove::new(4, 3, 3, 2)), "exd6 e.p.");
+        assert_eq!(game.to_san(&BoardMove::new(1, 1, 1, 0)), "b8=Q");
+        let promote_move = Move::new(BoardMove::new(1, 1, 2, 0), Some(ChessPieceId::Knight));
+        assert_eq!(game.move_to_san(&promote_move), "bxc8=N");
+
+        // Make sure parsing a move does not do the move
+        let mut game = Game::new();
+        game.set_up_board();
+        let chess_move = game.parse_san("Nf3").unwrap();
+        assert_eq!(chess_move.board_move.from_pos.to_algebraic_notation(), "g1");
+        assert_eq!(chess_move.board_move.to_pos.to_algebraic_notation(), "f3");
+        assert_eq!(chess_move.promote_piece.is_none(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("g1").unwrap()).is_some(), true);
+        assert_eq!(game.turn == ChessPieceColor::White, true);
+        assert_eq!(game.parse_san("Nf4").is_ok(), false);
+    }
+
+    #[test]
+    fn san_annotation_test() {
+        // Make sure you can castle using castling notation
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
+        game.set_pos(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("h1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        assert_eq!(game.algebraic_notation_move("O-O".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("g1").unwrap()).unwrap().id == ChessPieceId::King, true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("f1").unwrap()).unwrap().id == ChessPieceId::Rook, true);
+
+        // Make sure castling with zeros works, and that there must be a rook to castle with
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
+        game.turn = ChessPieceColor::Black;
+        game.set_pos(BoardPosition::from_algebraic_notation("e8").unwrap(), ChessPieceId::King, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("a8").unwrap(), ChessPieceId::Rook, ChessPieceColor::Black);
+        assert_eq!(game.algebraic_notation_move("0-0".to_string()).is_ok(), false);
+        assert_eq!(game.algebraic_notation_move("0-0-0+".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("c8").unwrap()).unwrap().id == ChessPieceId::King, true);
+
+        // Make sure check, check mate and move quality annotations are accepted
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.algebraic_notation_move("f3?".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("e5!".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("g4??".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("Qh4#!?".to_string()).is_ok(), true);
+        assert_eq!(game.is_check_mate(), true);
+
+        // Make sure en passant annotations are accepted
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_castling_rights(CastlingRights::all());
+        game.turn = ChessPieceColor::Black;
+        game.set_pos(BoardPosition::from_algebraic_notation("d7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("e5").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        assert_eq!(game.algebraic_notation_move("d5".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("exd6 e.p.".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("d5").unwrap()).is_some(), false);
+
+        // Make sure generated notation can be parsed again
+        let mut game = Game::new();
+        game.set_up_board();
+        for text in ["e4", "d5", "exd5", "Qxd5", "Nc3", "Qe5+", "Be2", "Bg4", "Nf3", "Bxf3", "O-O", "Nc6"] {
+            let chess_move = game.parse_san(text).unwrap();
+            assert_eq!(game.move_to_san(&chess_move), text);
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+    }
+
+    #[test]
+    fn uci_test() {
+        // Make sure moves can be converted to and from UCI notation
+        let board_move = BoardMove::from_uci("e2e4").unwrap();
+        assert_eq!(board_move.from_pos.to_algebraic_notation(), "e2");
+        assert_eq!(board_move.to_pos.to_algebraic_notation(), "e4");
+        assert_eq!(board_move.to_uci(), "e2e4");
+        let chess_move = Move::from_uci("e7e8q").unwrap();
+        assert_eq!(chess_move.promote_piece.unwrap() == ChessPieceId::Queen, true);
+        assert_eq!(chess_move.to_uci(), "e7e8q");
+        assert_eq!(Move::from_uci("a7a8n").unwrap().to_uci(), "a7a8n");
+
+        // Make sure program does not crash with wierd input
+        assert_eq!(BoardMove::from_uci("e2e9").is_ok(), false);
+        assert_eq!(BoardMove::from_uci("e2e4q").is_ok(), false);
+        assert_eq!(Move::from_uci("e7e8k").is_ok(), false);
+        assert_eq!(Move::from_uci("e7").is_ok(), false);
+
+        // Make sure lower case promotion works when moving
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("b7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        assert_eq!(game.algebraic_notation_move("b7b8n".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("b8").unwrap()).unwrap().id == ChessPieceId::Knight, true);
+
+        // Make sure upper case promotion still works when moving
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("e7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        assert_eq!(game.algebraic_notation_move("e7e8Q".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("e8").unwrap()).unwrap().id == ChessPieceId::Queen, true);
+
+        // Make sure parsing validates the move without doing it
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.parse_uci("e2e4").is_ok(), true);
+        assert_eq!(game.parse_uci("e2e5").is_ok(), false);
+        assert_eq!(game.parse_uci("e2e3q").is_ok(), false);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("e2").unwrap()).is_some(), true);
+
+        // Make sure castling is written as the king moving two squares, not as king takes rook
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("h1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        game.set_castling_rights(CastlingRights::all());
+        assert_eq!(game.parse_uci("e1h1").is_ok(), false);
+        assert_eq!(game.parse_uci("e1g1").unwrap().to_uci(), "e1g1");
+    }
+
+    #[test]
+    fn notation_locale_test() {
+        // Make sure swedish piece letters can be used
+        let mut game = Game::new();
+        game.set_up_board();
+        game.notation_locale = NotationLocale::Swedish;
+        assert_eq!(game.algebraic_notation_move("Sf3".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("f3").unwrap()).unwrap().id == ChessPieceId::Knight, true);
+        assert_eq!(game.parse_san("Sc6").unwrap().board_move.from_pos.to_algebraic_notation(), "b8");
+        assert_eq!(game.algebraic_notation_move("e5".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("e3".to_string()).is_ok(), true);
+        assert_eq!(game.to_san(&BoardMove::from_uci("d8g5").unwrap()), "Dg5");
+        assert_eq!(game.to_san(&BoardMove::from_uci("f8c5").unwrap()), "Lc5");
+
+        // Make sure german and french piece letters can be used
+        let mut game = Game::new();
+        game.set_up_board();
+        game.notation_locale = NotationLocale::German;
+        assert_eq!(game.to_san(&BoardMove::from_uci("g1f3").unwrap()), "Sf3");
+        game.notation_locale = NotationLocale::French;
+        assert_eq!(game.to_san(&BoardMove::from_uci("g1f3").unwrap()), "Cf3");
+        assert_eq!(game.algebraic_notation_move("Cf3".to_string()).is_ok(), true);
+
+        // Make sure promotion uses the local letters
+        let mut game = Game::new();
+        game.empty_board();
+        game.notation_locale = NotationLocale::Swedish;
+        game.set_pos(BoardPosition::from_algebraic_notation("a7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        assert_eq!(game.to_san(&BoardMove::from_uci("a7a8").unwrap()), "a8=D");
+        assert_eq!(game.algebraic_notation_move("a8T".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("a8").unwrap()).unwrap().id == ChessPieceId::Rook, true);
+
+        // Make sure localised piece names can be parsed
+        assert_eq!(ChessPieceId::from_str("Springare") == Ok(ChessPieceId::Knight), true);
+        assert_eq!(ChessPieceId::from_str("dam") == Ok(ChessPieceId::Queen), true);
+        assert_eq!(ChessPieceId::from_str("Läufer") == Ok(ChessPieceId::Bishop), true);
+        assert_eq!(ChessPieceId::from_str("tour") == Ok(ChessPieceId::Rook), true);
+        assert_eq!(ChessPieceId::from_str("horse").is_ok(), false);
+    }
+
+    #[test]
+    fn figurine_notation_test() {
+        // Make sure pieces get the glyph of their color
+        assert_eq!(ChessPiece::new(ChessPieceId::King, ChessPieceColor::White).unicode_char, '♔');
+        assert_eq!(ChessPiece::new(ChessPieceId::King, ChessPieceColor::Black).unicode_char, '♚');
+        assert_eq!(ChessPiece::new(ChessPieceId::Knight, ChessPieceColor::White).unicode_char, '♘');
+
+        // Make sure figurines are written with the color of the moving piece
+        let mut game = Game::new();
+        game.set_up_board();
+        game.notation_locale = NotationLocale::Figurine;
+        assert_eq!(game.to_san(&BoardMove::from_uci("g1f3").unwrap()), "♘f3");
+        assert_eq!(game.algebraic_notation_move("♘f3".to_string()).is_ok(), true);
+        assert_eq!(game.to_san(&BoardMove::from_uci("b8c6").unwrap()), "♞c6");
+        assert_eq!(game.algebraic_notation_move("♞c6".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+
+        // Make sure figurines of either color are accepted
+        assert_eq!(game.algebraic_notation_move("♘f6".to_string()).is_ok(), true);
+        assert_eq!(game.get_board_piece_clone(BoardPosition::from_algebraic_notation("f6").unwrap()).unwrap().id == ChessPieceId::Knight, true);
+
+        // Make sure captures, checks and promotions are written with figurines
+        let mut game = Game::new();
+        game.empty_board();
+        game.notation_locale = NotationLocale::Figurine;
+        game.set_pos(BoardPosition::from_algebraic_notation("d1").unwrap(), ChessPieceId::Queen, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("d7").unwrap(), ChessPieceId::Knight, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("e8").unwrap(), ChessPieceId::King, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("a7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        assert_eq!(game.to_san(&BoardMove::from_uci("d1d7").unwrap()), "♕xd7+");
+        assert_eq!(game.to_san(&BoardMove::from_uci("a7a8").unwrap()), "a8=♕+");
+        assert_eq!(game.algebraic_notation_move("♕xd7+".to_string()).is_ok(), true);
+    }
+
+    #[test]
+    fn read_only_query_test() {
+        // Make sure a game can be inspected through a shared reference
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+        let game = std::sync::Arc::new(game);
+        let game_ref: &Game = &game;
+        assert_eq!(game_ref.turn() == ChessPieceColor::Black, true);
+        assert_eq!(game_ref.last_move().unwrap().to_uci(), "e2e4");
+        assert_eq!(game_ref.piece_at(BoardPosition::from_algebraic_notation("e4").unwrap()).unwrap().id == ChessPieceId::Pawn, true);
+        assert_eq!(game_ref.piece_at(BoardPosition::from_algebraic_notation("e2").unwrap()).is_none(), true);
+        assert_eq!(game_ref.piece_at(BoardPosition::new(8, 0)).is_none(), true);
+        assert_eq!(game_ref.is_check().is_none(), true);
+        assert_eq!(game_ref.get_possible_moves().len(), 20);
+        assert_eq!(game_ref.get_winner().is_none(), true);
+        assert_eq!(game_ref.game_is_over(), false);
+        assert_eq!(game_ref.to_san(&BoardMove::from_uci("g8f6").unwrap()), "Nf6");
+
+        // Make sure the game can be queried from another thread
+        let game_clone = std::sync::Arc::clone(&game);
+        let handle = std::thread::spawn(move || {
+            return game_clone.get_possible_moves().len();
+        });
+        assert_eq!(handle.join().unwrap(), 20);
+
+        // Make sure the last move after castling is the king move
+        let mut game = Game::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+        assert_eq!(game.algebraic_notation_move("O-O".to_string()).is_ok(), true);
+        assert_eq!(game.last_move().unwrap().to_uci(), "e1g1");
+        assert_eq!(game.history().last().unwrap().chess_move.to_uci(), "e1g1");
+    }
+
+    #[test]
+    fn square_test() {
+        use std::convert::TryFrom;
+        use std::str::FromStr;
+
+        // Make sure squares convert to and from board positions
+        let pos: BoardPosition = Square::E4.into();
+        assert_eq!(pos.to_algebraic_notation(), "e4");
+        assert_eq!(Square::try_from(BoardPosition::from_algebraic_notation("a8").unwrap()) == Ok(Square::A8), true);
+        assert_eq!(Square::try_from(BoardPosition::new(8, 0)).is_ok(), false);
+        for square in Square::all() {
+            let pos: BoardPosition = square.into();
+            assert_eq!(Square::try_from(pos) == Ok(square), true);
+            assert_eq!(pos.to_algebraic_notation(), square.to_string());
+        }
+        assert_eq!(Square::all().count(), 64);
+
+        // Make sure files and ranks are read correctly
+        assert_eq!(Square::G7.file() == File::G, true);
+        assert_eq!(Square::G7.rank() == Rank::Seventh, true);
+        assert_eq!(Square::new(File::C, Rank::Second) == Square::C2, true);
+        assert_eq!(Square::from_str("h1") == Ok(Square::H1), true);
+        assert_eq!(Square::from_str("i1").is_ok(), false);
+        assert_eq!(Square::from_str("a10").is_ok(), false);
+        assert_eq!(File::from_str("d") == Ok(File::D), true);
+        assert_eq!(Rank::from_str("5") == Ok(Rank::Fifth), true);
+        assert_eq!(Rank::Eighth.to_string(), "8");
+
+        // Make sure moving outside the board gives none
+        assert_eq!(Square::A1.offset(1, 2) == Some(Square::B3), true);
+        assert_eq!(Square::A1.offset(-1, 0).is_none(), true);
+        assert_eq!(Square::H8.step(Direction::NorthEast).is_none(), true);
+        assert_eq!(Square::E4.step(Direction::SouthWest) == Some(Square::D3), true);
+        let ray: Vec<Square> = Square::C1.ray(Direction::NorthEast).collect();
+        assert_eq!(ray == vec![Square::D2, Square::E3, Square::F4, Square::G5, Square::H6], true);
+        assert_eq!(Square::A1.is_light(), false);
+        assert_eq!(Square::H1.is_light(), true);
+    }
+
+    #[test]
+    fn validate_test() {
+        // Make sure the start position is valid
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.validate().is_ok(), true);
+
+        // Make sure both kings are required
+        let mut game = Game::new();
+        game.empty_board();
+        let errors = game.validate().err().unwrap();
+        assert_eq!(errors.contains(&PositionError::MissingKing(ChessPieceColor::White)), true);
+        assert_eq!(errors.contains(&PositionError::MissingKing(ChessPieceColor::Black)), true);
+
+        // Make sure there can not be several kings, or pawns on the first rank
+        let mut game = Game::new();
+        game.set_up_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("e4").unwrap(), ChessPieceId::King, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("e5").unwrap(), ChessPieceId::King, ChessPieceColor::Black);
+        game.set_pos(BoardPosition::from_algebraic_notation("b1").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        let errors = game.validate().err().unwrap();
+        assert_eq!(errors.contains(&PositionError::TooManyKings(ChessPieceColor::Black)), true);
+        assert_eq!(errors.contains(&PositionError::TooManyPieces(ChessPieceColor::Black)), true);
+        assert_eq!(errors.contains(&PositionError::PawnOnBackRank(BoardPosition::from_algebraic_notation("b1").unwrap())), true);
+        assert_eq!(errors.contains(&PositionError::MissingKing(ChessPieceColor::White)), false);
+
+        // Make sure the player who is not to move can not be in check
+        let builder = PositionBuilder::new()
+            .piece(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::King, ChessPieceColor::White)
+            .piece(BoardPosition::from_algebraic_notation("e8").unwrap(), ChessPieceId::King, ChessPieceColor::Black)
+            .piece(BoardPosition::from_algebraic_notation("e4").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        assert_eq!(builder.build().err().unwrap() == vec![PositionError::OpponentInCheck], true);
+        let builder = builder.turn(ChessPieceColor::Black);
+        assert_eq!(builder.build().is_ok(), true);
+        let builder = builder.turn(ChessPieceColor::White);
+
+        // Make sure a built game can be played
+        let mut game = builder
+            .remove_piece(BoardPosition::from_algebraic_notation("e4").unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(game.algebraic_notation_move("Kd2".to_string()).is_ok(), true);
+    }
+
+    #[test]
+    fn position_builder_test() {
+        let pos = |text: &str| BoardPosition::from_algebraic_notation(text).unwrap();
+        let builder = PositionBuilder::new()
+            .piece(pos("e1"), ChessPieceId::King, ChessPieceColor::White)
+            .piece(pos("h1"), ChessPieceId::Rook, ChessPieceColor::White)
+            .piece(pos("a1"), ChessPieceId::Rook, ChessPieceColor::White)
+            .piece(pos("e8"), ChessPieceId::King, ChessPieceColor::Black);
+
+        // Make sure castling is only allowed when the rights are given
+        let mut game = builder.clone().build().unwrap();
+        assert_eq!(game.algebraic_notation_move("O-O".to_string()).is_ok(), false);
+        let mut game = builder.clone().castling_rights(ChessPieceColor::White, true, false).build().unwrap();
+        assert_eq!(game.parse_san("O-O-O").is_ok(), false);
+        assert_eq!(game.algebraic_notation_move("O-O".to_string()).is_ok(), true);
+
+        // Make sure castling rights require the king and rook to be in place
+        let result = builder.clone().castling_rights(ChessPieceColor::Black, true, true).build();
+        assert_eq!(result.err().unwrap() == vec![PositionError::InvalidCastlingRights(ChessPieceColor::Black)], true);
+
+        // Make sure the en passant square allows taking en passant
+        let builder = PositionBuilder::new()
+            .piece(pos("e1"), ChessPieceId::King, ChessPieceColor::White)
+            .piece(pos("e8"), ChessPieceId::King, ChessPieceColor::Black)
+            .piece(pos("d4"), ChessPieceId::Pawn, ChessPieceColor::White)
+            .piece(pos("e4"), ChessPieceId::Pawn, ChessPieceColor::Black)
+            .turn(ChessPieceColor::Black);
+        let game = builder.clone().build().unwrap();
+        assert_eq!(game.parse_san("exd3").is_ok(), false);
+        let mut game = builder.clone().en_passant(Some(pos("d3"))).build().unwrap();
+        assert_eq!(game.algebraic_notation_move("exd3".to_string()).is_ok(), true);
+        assert_eq!(game.piece_at(pos("d4")).is_none(), true);
+        let result = builder.clone().en_passant(Some(pos("e3"))).build();
+        assert_eq!(result.err().unwrap() == vec![PositionError::InvalidEnPassant(pos("e3"))], true);
+
+        // Make sure pawns that are not on their start rank can not move two squares
+        let mut game = builder.clone().turn(ChessPieceColor::White).build().unwrap();
+        assert_eq!(game.algebraic_notation_move("d6".to_string()).is_ok(), false);
+
+        // Make sure the move clocks are set and counted
+        let mut game = builder.clone().halfmove_clock(12).fullmove_number(30).build().unwrap();
+        assert_eq!(game.halfmove_clock(), 12);
+        assert_eq!(game.fullmove_number(), 30);
+        assert_eq!(game.algebraic_notation_move("Kf7".to_string()).is_ok(), true);
+        assert_eq!(game.halfmove_clock(), 13);
+        assert_eq!(game.fullmove_number(), 31);
+        assert_eq!(game.algebraic_notation_move("d5".to_string()).is_ok(), true);
+        assert_eq!(game.halfmove_clock(), 0);
+        assert_eq!(game.fullmove_number(), 31);
+
+        // Make sure a new game starts at move one
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.halfmove_clock(), 0);
+        assert_eq!(game.fullmove_number(), 1);
+    }
+
+    #[test]
+    fn castling_rights_test() {
+        let pos = |text: &str| BoardPosition::from_algebraic_notation(text).unwrap();
+
+        // Make sure a new game can castle on both sides
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.castling_rights() == CastlingRights::all(), true);
+
+        // Make sure moving the rook removes the right for that side only
+        assert_eq!(game.algebraic_notation_move("h4".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("a5".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("Rh3".to_string()).is_ok(), true);
+        assert_eq!(game.castling_rights().has(ChessPieceColor::White, CastleSide::KingSide), false);
+        assert_eq!(game.castling_rights().has(ChessPieceColor::White, CastleSide::QueenSide), true);
+
+        // Make sure moving the king removes both rights
+        assert_eq!(game.algebraic_notation_move("e5".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+        assert_eq!(game.algebraic_notation_move("Ke7".to_string()).is_ok(), true);
+        assert_eq!(game.castling_rights().has(ChessPieceColor::Black, CastleSide::KingSide), false);
+        assert_eq!(game.castling_rights().has(ChessPieceColor::Black, CastleSide::QueenSide), false);
+        assert_eq!(game.castling_rights().bits(), CastlingRights::WHITE_QUEEN_SIDE);
+
+        // Make sure taking a rook removes the right
+        let mut game = PositionBuilder::new()
+            .piece(pos("e1"), ChessPieceId::King, ChessPieceColor::White)
+            .piece(pos("e8"), ChessPieceId::King, ChessPieceColor::Black)
+            .piece(pos("h8"), ChessPieceId::Rook, ChessPieceColor::Black)
+            .piece(pos("b2"), ChessPieceId::Bishop, ChessPieceColor::White)
+            .castling_rights(ChessPieceColor::Black, true, false)
+            .build()
+            .unwrap();
+        assert_eq!(game.algebraic_notation_move("Bxh8".to_string()).is_ok(), true);
+        assert_eq!(game.castling_rights().is_empty(), true);
+
+        // Make sure rights can be restored in an editor
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(pos("e1"), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(pos("a1"), ChessPieceId::Rook, ChessPieceColor::White);
+        game.set_castling_rights(CastlingRights::none());
+        assert_eq!(game.parse_san("O-O-O").is_ok(), false);
+        game.set_castling_rights(CastlingRights::from_bits(CastlingRights::WHITE_QUEEN_SIDE));
+        assert_eq!(game.algebraic_notation_move("O-O-O".to_string()).is_ok(), true);
+
+        // Make sure a game built by hand starts without castling rights, so its FEN can be loaded again
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(pos("e1"), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(pos("e8"), ChessPieceId::King, ChessPieceColor::Black);
+        assert_eq!(game.castling_rights().is_empty(), true);
+        assert_eq!(game.to_fen(), "4k3/8/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(Game::from_fen(&game.to_fen()).is_ok(), true);
+
+        // Make sure castling is not allowed through other pieces
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.parse_san("O-O").is_ok(), false);
+
+        // Make sure castling is only done with the corner rook, not another rook closer to the king
+        let mut game = Game::new();
+        game.set_up_board();
+        for text in ["h4", "a6", "Rh3", "a5", "b4", "h6", "Rb3", "h5", "Nc3", "g6", "Rbb1", "c6", "Ba3", "c5", "e3", "d6", "Qe2", "e6"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.castling_rights().has(ChessPieceColor::White, CastleSide::QueenSide), true);
+        assert_eq!(game.parse_san("O-O-O").is_ok(), false);
+        assert_eq!(game.algebraic_notation_move("O-O-O".to_string()).is_ok(), false);
+        assert_eq!(game.get_board_piece_clone(pos("e1")).unwrap().id == ChessPieceId::King, true);
+        assert_eq!(game.get_board_piece_clone(pos("b1")).unwrap().id == ChessPieceId::Rook, true);
+        assert_eq!(game.legal_moves().iter().any(|chess_move| chess_move.to_uci() == "e1c1"), false);
+    }
+
+    #[test]
+    fn history_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.to_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        assert_eq!(game.history().is_empty(), true);
+
+        // Make sure moves are recorded with the position they lead to
+        for text in ["e4", "d5", "e5", "f5", "exf6", "Nc6", "fxg7", "Bf5"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        assert_eq!(game.history().len(), 8);
+        assert_eq!(game.history()[0].san, "e4");
+        assert_eq!(game.history()[0].fen, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        assert_eq!(game.history()[4].san, "exf6 e.p.");
+        assert_eq!(game.history()[0].captured.is_none(), true);
+
+        // Make sure the pawn taken en passant is recorded as captured
+        let captured = game.history()[4].captured.unwrap();
+        assert_eq!(captured.id == ChessPieceId::Pawn && captured.color == ChessPieceColor::Black, true);
+        assert_eq!(game.history()[6].captured.unwrap().id == ChessPieceId::Pawn, true);
+        assert_eq!(game.start_fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+
+        // Make sure comments and clock times can be added to the last move
+        assert_eq!(game.set_last_move_comment(Some("Developing".to_string())).is_ok(), true);
+        assert_eq!(game.set_last_move_clock(Some(std::time::Duration::from_secs(60))).is_ok(), true);
+        assert_eq!(game.history()[7].comment.clone().unwrap(), "Developing");
+        assert_eq!(game.history()[7].clock.unwrap().as_secs(), 60);
+
+        // Make sure testing moves does not add them to the history
+        assert_eq!(game.get_possible_moves().is_empty(), false);
+        assert_eq!(game.is_check_mate(), false);
+        assert_eq!(game.history().len(), 8);
+
+        // Make sure a new board clears the history
+        game.set_up_board();
+        assert_eq!(game.history().is_empty(), true);
+        assert_eq!(game.set_last_move_comment(None).is_ok(), false);
+
+        // Make sure the position hash is fixed, since it is stored with the history
+        assert_eq!(game.position_hash(), 0xe7550032f70614fc);
+
+        // Make sure repeated positions are counted
+        for _ in 0..2 {
+            for text in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+                assert_eq!(game.is_threefold_repetition(), false);
+                assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+            }
+        }
+        assert_eq!(game.repetition_count(), 3);
+        assert_eq!(game.is_threefold_repetition(), true);
+
+        // Make sure a promotion piece is not accepted, or recorded, on a move that does not promote
+        let mut game = Game::new();
+        game.set_up_board();
+        let board_move = BoardMove::from_uci("e2e4").unwrap();
+        assert_eq!(game.move_piece(board_move, true, Some(ChessPieceId::Queen)).unwrap_err(), "Cannot promote piece");
+        assert_eq!(game.move_piece(BoardMove::from_uci("e7e5").unwrap(), true, Some(ChessPieceId::Queen)).unwrap_err(), "Cannot move opponents pieces");
+        assert_eq!(game.history().is_empty(), true);
+        assert_eq!(game.move_piece(board_move, true, None).is_ok(), true);
+        assert_eq!(game.history()[0].chess_move.to_uci(), "e2e4");
+    }
+
+    #[test]
+    fn game_tree_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        let mut tree = GameTree::new(game);
+
+        // Make sure moves can be played and navigated
+        let e4 = tree.play_san("e4").unwrap();
+        let e5 = tree.play_san("e5").unwrap();
+        tree.play_san("Nf3").unwrap();
+        assert_eq!(tree.main_line().len(), 3);
+        assert_eq!(tree.back(), true);
+        assert_eq!(tree.current(), e5);
+        tree.go_to_start();
+        assert_eq!(tree.back(), false);
+        assert_eq!(tree.forward(), true);
+        assert_eq!(tree.current(), e4);
+        tree.go_to_end();
+        assert_eq!(tree.game().history().len(), 3);
+
+        // Make sure playing a known move goes to the existing node
+        tree.go_to(e4).unwrap();
+        assert_eq!(tree.play_san("e5").unwrap(), e5);
+
+        // Make sure another move adds a variation
+        tree.go_to(e4).unwrap();
+        let c5 = tree.play_san("c5").unwrap();
+        tree.play_san("Nf3").unwrap();
+        assert_eq!(tree.node(e4).unwrap().children().len(), 2);
+        assert_eq!(tree.play_san("Ke2").is_ok(), false);
+
+        // Make sure annotations are written to PGN with the variations
+        tree.node_mut(e4).unwrap().nags.push(1);
+        tree.node_mut(c5).unwrap().comment = Some("Sicilian".to_string());
+        tree.node_mut(c5).unwrap().arrows.push(Arrow {
+            from_pos: BoardPosition::from_algebraic_notation("g1").unwrap(),
+            to_pos: BoardPosition::from_algebraic_notation("f3").unwrap(),
+            color: MarkColor::Green,
+        });
+        assert_eq!(tree.to_pgn(), "[Result \"*\"]\n\n1. e4 $1 e5 (1... c5 {[%cal Gg1f3] Sicilian} 2. Nf3) 2. Nf3 *\n");
+
+        // Make sure a variation can become the main line
+        tree.promote_to_main_line(c5).unwrap();
+        assert_eq!(tree.main_line()[1], c5);
+        assert_eq!(tree.to_pgn(), "[Result \"*\"]\n\n1. e4 $1 c5 {[%cal Gg1f3] Sicilian} (1... e5 2. Nf3) 2. Nf3 *\n");
+
+        // Make sure deleting a variation moves away from deleted nodes
+        tree.go_to_end();
+        tree.delete_variation(c5).unwrap();
+        assert_eq!(tree.current(), e4);
+        assert_eq!(tree.node(c5).is_none(), true);
+        assert_eq!(tree.main_line().len(), 3);
+        assert_eq!(tree.delete_variation(tree.root()).is_ok(), false);
+
+        // Make sure the result is written when the game is over
+        let mut game = Game::new();
+        game.set_up_board();
+        let mut tree = GameTree::new(game);
+        for text in ["f3", "e5", "g4", "Qh4"] {
+            tree.play_san(text).unwrap();
+        }
+        assert_eq!(tree.to_pgn(), "[Result \"0-1\"]\n\n1. f3 e5 2. g4 Qh4# 0-1\n");
+    }
+
+    #[test]
+    fn clock_test() {
+        use std::time::Duration;
+        let secs = Duration::from_secs;
+
+        // Make sure Fischer increment is added after each move
+        let time = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeControl::fischer(secs(60), secs(2)), Box::new(time.clone()));
+        clock.start(ChessPieceColor::White);
+        time.advance(secs(10));
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(50));
+        assert_eq!(clock.press().is_ok(), true);
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(52));
+        assert_eq!(clock.running() == Some(ChessPieceColor::Black), true);
+
+        // Make sure Bronstein delay gives back at most the delay
+        let time = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeControl::bronstein(secs(60), secs(5)), Box::new(time.clone()));
+        clock.start(ChessPieceColor::White);
+        time.advance(secs(3));
+        clock.press().unwrap();
+        time.advance(secs(10));
+        clock.press().unwrap();
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(60));
+        assert_eq!(clock.remaining(ChessPieceColor::Black), secs(55));
+
+        // Make sure the simple delay passes before the clock counts down
+        let time = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeControl::simple_delay(secs(60), secs(5)), Box::new(time.clone()));
+        clock.start(ChessPieceColor::White);
+        time.advance(secs(4));
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(60));
+        time.advance(secs(4));
+        clock.press().unwrap();
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(57));
+
+        // Make sure the time of the next stage is added after the moves of a stage
+        let control: TimeControl = "2/5400:1800+30".parse().unwrap();
+        assert_eq!(control.to_string(), "2/5400:1800+30");
+        assert_eq!("40/x".parse::<TimeControl>().is_ok(), false);
+        let time = ManualTimeSource::new();
+        let mut clock = Clock::new(control, Box::new(time.clone()));
+        clock.start(ChessPieceColor::White);
+        for _ in 0..4 {
+            time.advance(secs(100));
+            clock.press().unwrap();
+        }
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(5400 - 200 + 1800));
+        time.advance(secs(100));
+        clock.press().unwrap();
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(5400 - 300 + 1800 + 30));
+
+        // Make sure the last stage is played again when its moves are done
+        let time = ManualTimeSource::new();
+        let mut clock = Clock::new("2/100:1/50".parse().unwrap(), Box::new(time.clone()));
+        clock.start(ChessPieceColor::White);
+        for _ in 0..6 {
+            time.advance(secs(10));
+            clock.press().unwrap();
+        }
+        assert_eq!(clock.remaining(ChessPieceColor::White), secs(100 - 30 + 50 + 50));
+
+        // Make sure running out of time ends the game
+        let time = ManualTimeSource::new();
+        let mut clock = Clock::new(TimeControl::sudden_death(secs(60)), Box::new(time.clone()));
+        let mut game = Game::new();
+        game.set_up_board();
+        clock.start(ChessPieceColor::White);
+        time.advance(secs(61));
+        assert_eq!(clock.flag_fall() == Some(ChessPieceColor::White), true);
+        assert_eq!(clock.press().is_ok(), false);
+        assert_eq!(game.update_from_clock(&clock), true);
+        assert_eq!(game.game_is_over(), true);
+        assert_eq!(game.get_winner() == Some(ChessPieceColor::Black), true);
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), false);
+
+        // Make sure running out of time is a draw if the opponent can not checkmate
+        let pos = |text: &str| BoardPosition::from_algebraic_notation(text).unwrap();
+        let game = PositionBuilder::new()
+            .piece(pos("e1"), ChessPieceId::King, ChessPieceColor::White)
+            .piece(pos("d1"), ChessPieceId::Queen, ChessPieceColor::White)
+            .piece(pos("e8"), ChessPieceId::King, ChessPieceColor::Black)
+            .piece(pos("b8"), ChessPieceId::Knight, ChessPieceColor::Black)
+            .build()
+            .unwrap();
+        assert_eq!(game.can_checkmate(ChessPieceColor::Black), true);
+        let mut game = PositionBuilder::new()
+            .piece(pos("e1"), ChessPieceId::King, ChessPieceColor::White)
+            .piece(pos("d1"), ChessPieceId::Queen, ChessPieceColor::White)
+            .piece(pos("e8"), ChessPieceId::King, ChessPieceColor::Black)
+            .build()
+            .unwrap();
+        game.set_flag_fall(ChessPieceColor::White);
+        assert_eq!(game.game_is_over(), true);
+        assert_eq!(game.get_winner().is_none(), true);
+    }
+
+    #[test]
+    fn from_fen_test() {
+        // Make sure FEN is read back to the same position
+        for fen in [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b Kq e3 0 3",
+            "8/8/8/4k3/8/8/8/4K2R w K - 12 40",
+        ] {
+            assert_eq!(Game::from_fen(fen).unwrap().to_fen(), fen);
+        }
+        let game = Game::from_fen("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 3").unwrap();
+        assert_eq!(game.parse_san("dxe3").is_ok(), true);
+
+        // Make sure invalid FEN is not accepted
+        assert_eq!(Game::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").is_ok(), false);
+        assert_eq!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq - 0 1").is_ok(), false);
+        assert_eq!(Game::from_fen("rnbqkbnrr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_ok(), false);
+        assert_eq!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR1 w KQkq - 0 1").is_ok(), false);
+        assert_eq!(Game::from_fen("rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").is_ok(), false);
+        assert_eq!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").is_ok(), false);
+        assert_eq!(Game::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").is_ok(), true);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        // Make sure a game is stored as FEN and moves, and can be loaded again
+        let mut game = Game::new();
+        game.set_up_board();
+        for text in ["e4", "e5", "Nf3"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        game.set_last_move_comment(Some("Attacking e5".to_string())).unwrap();
+        let json = serde_json::to_string(&game).unwrap();
+        assert_eq!(json.contains("\"fen\":\"rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2\""), true);
+        assert_eq!(json.contains("\"board\""), false);
+
+        let loaded: Game = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.to_fen(), game.to_fen());
+        assert_eq!(loaded.history().len(), 3);
+        assert_eq!(loaded.history()[2].san, "Nf3");
+        assert_eq!(loaded.history()[2].comment.clone().unwrap(), "Attacking e5");
+        assert_eq!(loaded.history()[0].timestamp == game.history()[0].timestamp, true);
+
+        // Make sure a game with moves that do not match the position is not loaded
+        let json = json.replace("e2e4", "e2e3");
+        assert_eq!(serde_json::from_str::<Game>(&json).is_ok(), false);
+
+        // Make sure games built by hand can be loaded again, even when from_fen would not accept the position
+        let game = Game::new();
+        let loaded: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(loaded.to_fen(), "8/8/8/8/8/8/8/8 w - - 0 1");
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("b7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("h8").unwrap(), ChessPieceId::Knight, ChessPieceColor::Black);
+        assert_eq!(game.algebraic_notation_move("b8=Q".to_string()).is_ok(), true);
+        let loaded: Game = serde_json::from_str(&serde_json::to_string(&game).unwrap()).unwrap();
+        assert_eq!(loaded.to_fen(), game.to_fen());
+        assert_eq!(loaded.start_fen(), "7n/1P6/8/8/8/8/8/4K3 w - - 0 1");
+        assert_eq!(loaded.history()[0].san, "b8=Q");
+
+        // Make sure the other types can be stored
+        let board_move = BoardMove::new(4, 6, 4, 4);
+        let json = serde_json::to_string(&board_move).unwrap();
+        let loaded: BoardMove = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.to_uci(), "e2e4");
+        let piece: ChessPiece = serde_json::from_str(&serde_json::to_string(&ChessPiece::new(ChessPieceId::Queen, ChessPieceColor::Black)).unwrap()).unwrap();
+        assert_eq!(piece.id == ChessPieceId::Queen && piece.color == ChessPieceColor::Black, true);
+    }
+
+    #[test]
+    fn encoding_test() {
+        // Make sure moves fit in 16 bits and decode to the same move
+        let board_move = BoardMove::from_uci("e2e4").unwrap();
+        assert_eq!(board_move.encode(), 12 | 28 << 6);
+        assert_eq!(BoardMove::decode(board_move.encode()).to_uci(), "e2e4");
+        let promotion = Move::from_uci("a7a8n").unwrap();
+        assert_eq!(promotion.encode() & MOVE_FLAG_CASTLE, MOVE_FLAG_PROMOTION);
+        assert_eq!(Move::decode(promotion.encode()).to_uci(), "a7a8n");
+
+        // Make sure castling and en passant are flagged
+        let game = Game::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        assert_eq!(game.encode_move(&Move::from_uci("e1g1").unwrap()) & MOVE_FLAG_CASTLE, MOVE_FLAG_CASTLE);
+        assert_eq!(game.encode_move(&Move::from_uci("e5d6").unwrap()) & MOVE_FLAG_CASTLE, MOVE_FLAG_EN_PASSANT);
+        assert_eq!(game.encode_move(&Move::from_uci("e5e6").unwrap()) & MOVE_FLAG_CASTLE, 0);
+
+        // Make sure positions are packed and unpacked
+        let packed = game.encode_position();
+        assert_eq!(packed.len(), PACKED_POSITION_SIZE);
+        assert_eq!(Game::decode_position(&packed).unwrap().to_fen(), game.to_fen());
+        assert_eq!(Game::decode_position(&packed[1..]).is_ok(), false);
+
+        // Make sure a game is stored with one byte per move
+        let mut game = Game::new();
+        game.set_up_board();
+        for text in ["e4", "d5", "exd5", "c6", "dxc6", "Qd7", "cxb7", "Kd8", "bxa8=N"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        let bytes = game.encode_game().unwrap();
+        assert_eq!(bytes.len(), 10);
+        let decoded = Game::decode_game(&bytes).unwrap();
+        assert_eq!(decoded.to_fen(), game.to_fen());
+        assert_eq!(decoded.history().len(), 9);
+        assert_eq!(decoded.history()[8].san, "bxa8=N");
+        assert_eq!(Game::decode_game(&[0, 255]).is_ok(), false);
+
+        // Make sure games from other positions store the start position
+        let mut game = Game::from_fen("4k3/P7/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_eq!(game.algebraic_notation_move("a8=Q".to_string()).is_ok(), true);
+        let bytes = game.encode_game().unwrap();
+        assert_eq!(bytes.len(), 1 + PACKED_POSITION_SIZE + 1);
+        assert_eq!(Game::decode_game(&bytes).unwrap().to_fen(), game.to_fen());
+
+        // Make sure games built by hand are decoded again, even when PositionBuilder would not accept the position
+        let game = Game::new();
+        assert_eq!(Game::decode_position(&game.encode_position()).unwrap().to_fen(), "8/8/8/8/8/8/8/8 w - - 0 1");
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("e1").unwrap(), ChessPieceId::King, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("b7").unwrap(), ChessPieceId::Pawn, ChessPieceColor::White);
+        game.set_pos(BoardPosition::from_algebraic_notation("h8").unwrap(), ChessPieceId::Knight, ChessPieceColor::Black);
+        assert_eq!(Game::decode_position(&game.encode_position()).unwrap().to_fen(), game.to_fen());
+        assert_eq!(game.algebraic_notation_move("b8=Q".to_string()).is_ok(), true);
+        let decoded = Game::decode_game(&game.encode_game().unwrap()).unwrap();
+        assert_eq!(decoded.to_fen(), game.to_fen());
+        assert_eq!(decoded.history()[0].san, "b8=Q");
+    }
+
+    #[test]
+    fn render_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+
+        // Make sure the board can be rendered to a string
+        let text = AsciiRenderer.render_to_string(&game, &RenderOptions::default());
+        assert_eq!(text.lines().next().unwrap(), "  a b c d e f g h");
+        assert_eq!(text.lines().nth(1).unwrap(), "8 r n b q k b n r 8");
+        assert_eq!(text.lines().nth(8).unwrap(), "1 R N B Q K B N R 1");
+        assert_eq!(format!("{:#}", game), text);
+
+        // Make sure the board can be drawn from black's side without coordinates
+        let options = RenderOptions {
+            orientation: ChessPieceColor::Black,
+            coordinates: false,
+            ..Default::default()
+        };
+        let text = UnicodeRenderer.render_to_string(&game, &options);
+        assert_eq!(text.lines().next().unwrap(), "♖ ♘ ♗ ♔ ♕ ♗ ♘ ♖ ");
+        assert_eq!(text.lines().nth(3).unwrap(), "· · · · · · · · ");
+
+        // Make sure highlights, the selected square and the last move are marked
+        assert_eq!(game.algebraic_notation_move("e4".to_string()).is_ok(), true);
+        let options = RenderOptions {
+            coordinates: false,
+            highlights: vec![BoardPosition::from_algebraic_notation("f6").unwrap()],
+            selected: Some(BoardPosition::from_algebraic_notation("g8").unwrap()),
+            mark_last_move: true,
+            ..Default::default()
+        };
+        let text = AsciiRenderer.render_to_string(&game, &options);
+        assert_eq!(text.lines().next().unwrap(), "r n b q k b n+r ");
+        assert_eq!(text.lines().nth(2).unwrap(), ". . . . . .*. . ");
+        assert_eq!(text.lines().nth(4).unwrap(), ". . . . P~. . . ");
+        assert_eq!(text.lines().nth(6).unwrap(), "P P P P .~P P P ");
+        let text = AnsiRenderer.render_to_string(&game, &options);
+        assert_eq!(text.contains("\x1b[42m\x1b[30m♞"), true);
+
+        // Make sure games, moves and positions can be written and read as text
+        assert_eq!(game.to_string(), "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+        let loaded: Game = game.to_string().parse().unwrap();
+        assert_eq!(loaded.to_fen(), game.to_fen());
+        assert_eq!("e7e8q".parse::<Move>().unwrap().to_string(), "e7e8q");
+        assert_eq!("g1f3".parse::<BoardMove>().unwrap().to_string(), "g1f3");
+        assert_eq!("h8".parse::<BoardPosition>().unwrap().to_string(), "h8");
+    }
+
+    #[test]
+    fn svg_test() {
+        let pos = |text: &str| BoardPosition::from_algebraic_notation(text).unwrap();
+        let mut game = Game::new();
+        game.set_up_board();
+        for text in ["e4", "e5", "Nf3", "Nc6", "Bc4"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        let options = SvgOptions {
+            arrows: vec![Arrow { from_pos: pos("f3"), to_pos: pos("g5"), color: MarkColor::Green }],
+            highlights: vec![Highlight { pos: pos("f7"), color: MarkColor::Red }],
+            ..Default::default()
+        };
+
+        // Make sure the diagram matches the stored snapshot, run with UPDATE_SNAPSHOTS=1 to store a new one
+        let snapshots = [
+            ("tests/snapshots/italian_game.svg", game.to_svg(&options)),
+            ("tests/snapshots/italian_game_flipped.svg", game.to_svg(&SvgOptions {
+                orientation: ChessPieceColor::Black,
+                coordinates: false,
+                mark_last_move: false,
+                ..options.clone()
+            })),
+        ];
+        for (path, svg) in snapshots.iter() {
+            if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+                std::fs::write(path, svg).unwrap();
+            }
+            assert_eq!(svg, &std::fs::read_to_string(path).unwrap());
+        }
+        assert_eq!(snapshots[0].1.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""), true);
+        assert_eq!(snapshots[0].1.matches("<use ").count(), 32);
+    }
+
+    #[test]
+    fn search_test() {
+        // Make sure the score is the same for both sides in the start position
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.evaluate(), 0);
+
+        // Make sure the move picker finds a mate in one
+        let game = Game::from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1").unwrap();
+        let (chess_move, score) = game.search(DEFAULT_SEARCH_DEPTH).unwrap();
+        assert_eq!(chess_move.to_uci(), "a1a8");
+        assert_eq!(score > MATE_SCORE / 2, true);
+
+        // Make sure the move picker takes a free queen and sees that it is losing
+        let game = Game::from_fen("4k3/8/8/3q4/8/8/3R4/K7 b - - 0 1").unwrap();
+        assert_eq!(game.best_move(DEFAULT_SEARCH_DEPTH).unwrap().to_uci(), "d5d2");
+        let game = Game::from_fen("4k3/8/8/3q4/8/8/3R4/K7 w - - 0 1").unwrap();
+        assert_eq!(game.evaluate() < 0, true);
+        assert_eq!(game.best_move(DEFAULT_SEARCH_DEPTH).unwrap().to_uci(), "d2d5");
+
+        // Make sure there is no move when the game is over
+        let game = Game::from_fen("R5k1/5ppp/8/8/8/8/5PPP/6K1 b - - 1 1").unwrap();
+        assert_eq!(game.best_move(DEFAULT_SEARCH_DEPTH).is_none(), true);
+    }
+
+    #[test]
+    fn undo_pgn_test() {
+        let mut game = Game::new();
+        game.set_up_board();
+        assert_eq!(game.undo_move().is_ok(), false);
+
+        // Make sure undoing a move restores the position before it
+        for text in ["e4", "e5", "Nf3"] {
+            assert_eq!(game.algebraic_notation_move(text.to_string()).is_ok(), true);
+        }
+        let entry = game.undo_move().unwrap();
+        assert_eq!(entry.san, "Nf3");
+        assert_eq!(game.to_fen(), "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+        assert_eq!(game.history().len(), 2);
+        assert_eq!(game.turn == ChessPieceColor::White, true);
+
+        // Make sure a game is written with the seven tags and read back
+        assert_eq!(game.set_last_move_comment(Some("Open game".to_string())).is_ok(), true);
+        let pgn = game.to_pgn();
+        assert_eq!(pgn.starts_with("[Event \"?\"]"), true);
+        assert_eq!(pgn.contains("1. e4 e5 {Open game} *"), true);
+        let read_game = Game::from_pgn(&pgn).unwrap();
+        assert_eq!(read_game.to_fen(), game.to_fen());
+        assert_eq!(read_game.history()[1].comment.clone().unwrap(), "Open game");
+
+        // Make sure games from a position, variations, annotations and comments are read
+        let pgn = "[FEN \"4k3/8/8/8/8/8/4P3/4K3 w - - 0 1\"]\n\n1. e4 $1 (1. e3 {slower}) Kd7 ; king comes\n2.Kd2 1-0";
+        let read_game = Game::from_pgn(pgn).unwrap();
+        assert_eq!(read_game.to_fen(), "8/3k4/8/8/4P3/8/3K4/8 b - - 2 2");
+        assert_eq!(Game::from_pgn("1. e4 e5 2. Ke3").is_ok(), false);
+
+        // Make sure a position set up by hand, that FEN validation refuses, can be written and taken back
+        let pos = |text: &str| BoardPosition::from_algebraic_notation(text).unwrap();
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(pos("a1"), ChessPieceId::Rook, ChessPieceColor::White);
+        assert_eq!(Game::from_fen(&game.to_fen()).is_ok(), false);
+        assert_eq!(game.move_piece(BoardMove { from_pos: pos("a1"), to_pos: pos("a5") }, true, None).is_ok(), true);
+        assert_eq!(game.to_pgn().contains("1. Ra5 "), true);
+        assert_eq!(game.undo_move().unwrap().san, "Ra5");
+        assert_eq!(game.piece_at(pos("a1")).is_some(), true);
+        assert_eq!(game.history().is_empty(), true);
+    }
+
+    #[test]
+    fn command_test() {
+        // Make sure commands are read with typed errors
+        assert_eq!("undo".parse::<Command>().unwrap(), Command::Undo);
+        assert_eq!(" Nf3 ".parse::<Command>().unwrap(), Command::Move("Nf3".to_string()));
+        assert_eq!("load 8/8/8/8/8/8/8/K6k w - - 0 1".parse::<Command>().unwrap(), Command::Load("8/8/8/8/8/8/8/K6k w - - 0 1".to_string()));
+        assert_eq!("pgn save game.pgn".parse::<Command>().unwrap(), Command::PgnSave("game.pgn".to_string()));
+        assert_eq!("".parse::<Command>().unwrap_err(), CommandError::Empty);
+        assert_eq!("moves".parse::<Command>().unwrap_err(), CommandError::MissingArgument("square"));
+        assert_eq!("moves i9".parse::<Command>().unwrap_err(), CommandError::InvalidSquare("i9".to_string()));
+        assert_eq!("play e4".parse::<Command>().unwrap_err(), CommandError::UnknownCommand("play".to_string()));
+
+        // Make sure the computer answers moves and undo takes back both
+        let mut game = Game::new();
+        game.set_up_board();
+        let mut session = CommandSession::new(game);
+        session.computer = Some(ChessPieceColor::Black);
+        assert_eq!(session.execute_line("e4").unwrap().contains("Computer plays"), true);
+        assert_eq!(session.game.history().len(), 2);
+        assert_eq!(session.execute(Command::Undo).is_ok(), true);
+        assert_eq!(session.game.history().is_empty(), true);
+        assert_eq!(session.execute(Command::Undo).unwrap_err(), CommandError::NothingToUndo);
+
+        // Make sure the history of a position set up by hand, that FEN validation refuses, can be listed
+        let mut game = Game::new();
+        game.empty_board();
+        game.set_pos(BoardPosition::from_algebraic_notation("a1").unwrap(), ChessPieceId::Rook, ChessPieceColor::White);
+        let mut session = CommandSession::new(game);
+        assert_eq!(session.execute_line("Ra5").is_ok(), true);
+        assert_eq!(session.execute(Command::History).unwrap(), "1. Ra5\n");
+
+        // Make sure a scripted session gives the stored output, run with UPDATE_SNAPSHOTS=1 to store a new one
+        let mut game = Game::new();
+        game.set_up_board();
+        let mut session = CommandSession::new(game);
+        session.colored = false;
+        let output = session.run_script(&std::fs::read_to_string("tests/scripts/cli_session.txt").unwrap());
+        let path = "tests/snapshots/cli_session.txt";
+        if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+            std::fs::write(path, &output).unwrap();
+        }
+        assert_eq!(output, std::fs::read_to_string(path).unwrap());
+        assert_eq!(session.result, Some(SessionResult::DrawAgreed));
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn server_test() {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpStream;
+        use std::time::Duration;
+
+        let server = PlayServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+
+        // Clients of the line protocol
+        let connect = || {
+            let stream = TcpStream::connect(address).unwrap();
+            stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            return (stream.try_clone().unwrap(), BufReader::new(stream));
+        };
+        let send = |stream: &mut TcpStream, message: ClientMessage| writeln!(stream, "{}", message.to_line()).unwrap();
+        let receive = |reader: &mut BufReader<TcpStream>| {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            return ServerMessage::from_line(&line).unwrap();
+        };
+
+        // Make sure a game can be created with a clock
+        let (mut white, mut white_reader) = connect();
+        send(&mut white, ClientMessage::Create { color: Some(ChessPieceColor::White), time_control: Some("300+2".to_string()) });
+        let (code, token) = match receive(&mut white_reader) {
+            ServerMessage::Created { code, color: ChessPieceColor::White, token } => (code, token),
+            message => panic!("Unexpected message {:?}", message),
+        };
+        send(&mut white, ClientMessage::Move { text: "e4".to_string() });
+        assert_eq!(receive(&mut white_reader), ServerMessage::Error { message: "Waiting for an opponent".to_string() });
+
+        // Make sure a WebSocket client can join with the code, which starts the game
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let (mut black, _) = tungstenite::client(format!("ws://{}/", address), stream).unwrap();
+        let send_json = |black: &mut tungstenite::WebSocket<TcpStream>, message: ClientMessage| {
+            black.send(tungstenite::Message::Text(serde_json::to_string(&message).unwrap())).unwrap();
+        };
+        let receive_json = |black: &mut tungstenite::WebSocket<TcpStream>| loop {
+            if let tungstenite::Message::Text(text) = black.read().unwrap() {
+                return serde_json::from_str::<ServerMessage>(&text).unwrap();
+            }
+        };
+        send_json(&mut black, ClientMessage::Join { code: "ZZZZ".to_string() });
+        send_json(&mut black, ClientMessage::Join { code: code.clone() });
+        assert_eq!(receive_json(&mut black), ServerMessage::Error { message: "No game with that code".to_string() });
+        assert_eq!(matches!(receive_json(&mut black), ServerMessage::Joined { color: ChessPieceColor::Black, .. }), true);
+        let start_fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string();
+        for message in [receive_json(&mut black), receive(&mut white_reader)] {
+            match message {
+                ServerMessage::State { fen, white_ms, moves, .. } => {
+                    assert_eq!(fen, start_fen);
+                    assert_eq!(white_ms.unwrap() <= 300000 && white_ms.unwrap() > 290000, true);
+                    assert_eq!(moves.is_empty(), true);
+                }
+                message => panic!("Unexpected message {:?}", message),
+            }
+        }
+
+        // Make sure spectators see the game and moves are checked
+        let (mut spectator, mut spectator_reader) = connect();
+        send(&mut spectator, ClientMessage::Watch { code: code.clone() });
+        assert_eq!(receive(&mut spectator_reader), ServerMessage::Watching { code: code.clone() });
+        assert_eq!(matches!(receive(&mut spectator_reader), ServerMessage::State { .. }), true);
+        send(&mut spectator, ClientMessage::Move { text: "e4".to_string() });
+        assert_eq!(receive(&mut spectator_reader), ServerMessage::Error { message: "Spectators can not play".to_string() });
+        send_json(&mut black, ClientMessage::Move { text: "e5".to_string() });
+        assert_eq!(receive_json(&mut black), ServerMessage::Error { message: "It is not your turn".to_string() });
+        send(&mut white, ClientMessage::Move { text: "e5".to_string() });
+        assert_eq!(matches!(receive(&mut white_reader), ServerMessage::Error { .. }), true);
+        send(&mut white, ClientMessage::Move { text: "e2e4".to_string() });
+        for message in [receive(&mut white_reader), receive(&mut spectator_reader), receive_json(&mut black)] {
+            match message {
+                ServerMessage::Moved { uci, san, white_ms, .. } => {
+                    assert_eq!(uci, "e2e4");
+                    assert_eq!(san, "e4");
+                    // The increment is added after the move
+                    assert_eq!(white_ms.unwrap() > 300000, true);
+                }
+                message => panic!("Unexpected message {:?}", message),
+            }
+        }
+
+        // Make sure a player can take the seat back after losing the connection
+        drop(white_reader);
+        white.shutdown(std::net::Shutdown::Both).unwrap();
+        assert_eq!(receive_json(&mut black), ServerMessage::OpponentLeft);
+        let (mut white, mut white_reader) = connect();
+        send(&mut white, ClientMessage::Resume { code: code.clone(), token: "wrong".to_string() });
+        assert_eq!(receive(&mut white_reader), ServerMessage::Error { message: "Invalid token".to_string() });
+        send(&mut white, ClientMessage::Resume { code: code.clone(), token: token.clone() });
+        assert_eq!(receive(&mut white_reader), ServerMessage::Resumed { code: code.clone(), color: ChessPieceColor::White });
+        match receive(&mut white_reader) {
+            ServerMessage::State { moves, .. } => assert_eq!(moves, vec!["e2e4".to_string()]),
+            message => panic!("Unexpected message {:?}", message),
+        }
+        assert_eq!(receive_json(&mut black), ServerMessage::OpponentReturned);
+
+        // Make sure only the connection that resumed last can play for the seat
+        let (mut old_white, mut old_white_reader) = (white, white_reader);
+        let (mut white, mut white_reader) = connect();
+        send(&mut white, ClientMessage::Resume { code: code.clone(), token });
+        assert_eq!(receive(&mut white_reader), ServerMessage::Resumed { code: code.clone(), color: ChessPieceColor::White });
+        assert_eq!(matches!(receive(&mut white_reader), ServerMessage::State { .. }), true);
+        assert_eq!(receive_json(&mut black), ServerMessage::OpponentReturned);
+        send(&mut old_white, ClientMessage::Resign);
+        let seat_taken = ServerMessage::Error { message: "The seat has been resumed from another connection".to_string() };
+        assert_eq!(receive(&mut old_white_reader), seat_taken);
+
+        // Make sure everyone is told when the game ends
+        send_json(&mut black, ClientMessage::Resign);
+        let game_over = ServerMessage::GameOver { result: "1-0".to_string(), reason: "resignation".to_string() };
+        assert_eq!(receive_json(&mut black), game_over);
+        assert_eq!(receive(&mut white_reader), game_over);
+        assert_eq!(receive(&mut spectator_reader), game_over);
+
+        // Make sure a finished game is removed when everyone has left
+        for stream in [&white, &old_white, &spectator] {
+            stream.shutdown(std::net::Shutdown::Both).unwrap();
+        }
+        black.close(None).unwrap();
+        let mut removed = false;
+        for _ in 0..50 {
+            let (mut watcher, mut watcher_reader) = connect();
+            send(&mut watcher, ClientMessage::Watch { code: code.clone() });
+            if receive(&mut watcher_reader) == (ServerMessage::Error { message: "No game with that code".to_string() }) {
+                removed = true;
+                break;
+            }
+            watcher.shutdown(std::net::Shutdown::Both).unwrap();
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(removed, true);
+
+        // Make sure the line protocol reads what it writes
+        let message = ServerMessage::Moved { uci: "g1f3".to_string(), san: "Nf3".to_string(), fen: start_fen.clone(), white_ms: None, black_ms: Some(1500) };
+        assert_eq!(ServerMessage::from_line(&message.to_line()).unwrap(), message);
+        assert_eq!(ClientMessage::from_line("create random").unwrap(), ClientMessage::Create { color: None, time_control: None });
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_api_test() {
+        use std::io::{Read, Write};
+
+        let server = ApiServer::bind("127.0.0.1:0").unwrap();
+        let address = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run());
+
+        // Send a request and get the status code and body of the response
+        let post = |path: &str, body: &str| -> (u16, String) {
+            let mut stream = std::net::TcpStream::connect(address).unwrap();
+            write!(stream, "POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                path, body.len(), body).unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            let (head, body) = response.split_once("\r\n\r\n").unwrap();
+            return (head[9..12].parse().unwrap(), body.to_string());
+        };
+
+        // Make sure the legal moves of a position are listed, with promotions to each piece
+        let (status, body) = post("/legal-moves", r#"{"fen": "4k3/P7/8/8/8/8/8/4K3 w - - 0 1"}"#);
+        assert_eq!(status, 200);
+        let legal_moves: ApiLegalMoves = serde_json::from_str(&body).unwrap();
+        assert_eq!(legal_moves.moves.len(), 9);
+        assert_eq!(legal_moves.moves.contains(&ApiMove { uci: "a7a8q".to_string(), san: "a8=Q+".to_string() }), true);
+
+        // Make sure a move is applied after the move list
+        let (status, body) = post("/apply-move", r#"{"moves": ["f2f3", "e5", "g4"], "move": "Qh4"}"#);
+        assert_eq!(status, 200);
+        let applied: ApiAppliedMove = serde_json::from_str(&body).unwrap();
+        assert_eq!(applied.chess_move, ApiMove { uci: "d8h4".to_string(), san: "Qh4#".to_string() });
+        assert_eq!(applied.status.checkmate && applied.status.game_over, true);
+        assert_eq!(applied.status.result, "0-1");
+
+        // Make sure the status of the start position is given without a body
+        let (status, body) = post("/status", "");
+        assert_eq!(status, 200);
+        let game_status: ApiStatus = serde_json::from_str(&body).unwrap();
+        assert_eq!(game_status.turn, ChessPieceColor::White);
+        assert_eq!(game_status.result, "*");
+
+        // Make sure the search finds a mate in one
+        let (_, body) = post("/best-move", r#"{"fen": "6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1"}"#);
+        let best: ApiBestMove = serde_json::from_str(&body).unwrap();
+        assert_eq!(best.chess_move.unwrap().san, "Ra8#");
+        assert_eq!(best.score.unwrap() > MATE_SCORE / 2, true);
+
+        // Make sure a board diagram is drawn
+        let (status, body) = post("/render.svg", r#"{"orientation": "Black", "size": 200}"#);
+        assert_eq!(status, 200);
+        assert_eq!(body.contains(r#"width="200""#), true);
+
+        // Make sure errors are reported as JSON
+        let errors = [
+            ("/apply-move", r#"{"move": "e5"}"#, 400),
+            ("/status", r#"{"moves": ["e2e4", "e2e4"]}"#, 400),
+            ("/status", r#"{"fen": "8/8/8 w - -"}"#, 400),
+            ("/best-move", r#"{"depth": 9}"#, 400),
+            ("/status", "not json", 400),
+            ("/evaluate", "{}", 404),
+        ];
+        for (path, body, expected_status) in errors {
+            let (status, body) = post(path, body);
+            assert_eq!(status, expected_status);
+            assert_eq!(serde_json::from_str::<ApiError>(&body).is_ok(), true);
+        }
+        assert_eq!(handle_api_request("GET", "/status", "").status, 405);
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn wasm_test() {
+        // Make sure the bindings play moves in both notations and report the outcome
+        let mut game = WasmGame::new();
+        assert_eq!(game.legal_moves().len(), 20);
+        assert_eq!(game.legal_moves_san().contains(&"Nf3".to_string()), true);
+        assert_eq!(game.make_move("f2f3").unwrap(), "f3");
+        assert_eq!(game.make_move("e5").unwrap(), "e5");
+        assert_eq!(game.make_move("g4").unwrap(), "g4");
+        assert_eq!(game.make_move("Ke2").is_ok(), false);
+        assert_eq!(game.turn(), "black");
+        assert_eq!(game.outcome(), "*");
+        assert_eq!(game.make_move("Qh4").unwrap(), "Qh4#");
+        assert_eq!(game.is_check() && game.is_checkmate() && game.is_game_over(), true);
+        assert_eq!(game.outcome(), "0-1");
+        assert_eq!(game.history(), vec!["f3", "e5", "g4", "Qh4#"]);
+        assert_eq!(game.undo().unwrap(), "Qh4#");
+        assert_eq!(game.is_game_over(), false);
+
+        // Make sure games can be loaded and drawn
+        let game = WasmGame::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(game.is_stalemate(), true);
+        assert_eq!(game.outcome(), "1/2-1/2");
+        assert_eq!(WasmGame::from_fen("8/8 w").is_ok(), false);
+        assert_eq!(game.to_svg("black", 240).unwrap().contains(r#"width="240""#), true);
+        assert_eq!(game.to_svg("up", 240).is_ok(), false);
+        let game = WasmGame::from_pgn(&WasmGame::new().to_pgn()).unwrap();
+        assert_eq!(game.fen(), "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi_test() {
+        // Make sure every rule error from Game::move_piece gets its own code, so changed messages are noticed
+        let get_code = |fen: &str, chess_move: Move| {
+            let mut game = Game::from_fen(fen).unwrap();
+            return get_move_error_code(&game.move_piece(chess_move.board_move, true, chess_move.promote_piece).unwrap_err());
+        };
+        let uci = |text: &str| Move::from_uci(text).unwrap();
+        let start_fen = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        assert_eq!(get_code(start_fen, uci("e3e4")), CHESS_ERROR_NO_PIECE);
+        assert_eq!(get_code(start_fen, uci("e7e5")), CHESS_ERROR_NOT_YOUR_PIECE);
+        assert_eq!(get_code(start_fen, uci("d1d2")), CHESS_ERROR_OWN_PIECE_ON_TARGET);
+        assert_eq!(get_code("4k3/8/8/8/8/8/4r3/4K3 w - - 0 1", uci("e1d2")), CHESS_ERROR_LEAVES_KING_IN_CHECK);
+        assert_eq!(get_code("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", uci("a7a8")), CHESS_ERROR_PROMOTION);
+        assert_eq!(get_code("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", Move::new(BoardMove::from_uci("a7a8").unwrap(), Some(ChessPieceId::King))), CHESS_ERROR_PROMOTION);
+        assert_eq!(get_code(start_fen, uci("e2e4q")), CHESS_ERROR_PROMOTION);
+        assert_eq!(get_code("4k3/8/8/8/8/8/8/R3K3 w - - 100 80", uci("a1a2")), CHESS_ERROR_GAME_OVER);
+        assert_eq!(get_code(start_fen, uci("e2e5")), CHESS_ERROR_ILLEGAL_MOVE);
+        let mut game = Game::from_fen(start_fen).unwrap();
+        game.set_flag_fall(ChessPieceColor::White);
+        let chess_move = uci("e2e4");
+        let error = game.move_piece(chess_move.board_move, true, chess_move.promote_piece).unwrap_err();
+        assert_eq!(get_move_error_code(&error), CHESS_ERROR_GAME_OVER);
+
+        // Build the C test program against the library built with the tests, which is next to the test binary, and run it
+        let manifest_dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR"));
+        let library_dir = std::env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        let program = library_dir.join("test_ffi");
+        let status = std::process::Command::new("cc")
+            .arg(manifest_dir.join("tests/ffi/test_ffi.c"))
+            .arg("-I")
+            .arg(manifest_dir.join("include"))
+            .arg("-L")
+            .arg(&library_dir)
+            .arg("-lchess_engine")
+            .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+            .arg("-o")
+            .arg(&program)
+            .status()
+            .unwrap();
+        assert_eq!(status.success(), true);
+        // Cargo's library path can hold a copy of the library built without the ffi feature, use the rpath instead
+        let output = std::process::Command::new(&program).env_remove("LD_LIBRARY_PATH").output().unwrap();
+        assert_eq!(output.status.success(), true, "{:?} {}{}", output.status, String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    }
+
+    #[cfg(feature = "python")]
+    #[test]
+    fn python_test() {
+        // Make sure the bindings replay games and take moves back
+        let mut game = PyGame::new(None).unwrap();
+        assert_eq!(game.legal_moves().len(), 20);
+        assert_eq!(game.push_san("f3").unwrap().uci(), "f2f3");
+        assert_eq!(game.push_uci("e7e5").unwrap().__repr__(), "BoardMove('e7e5')");
+        let g4 = PyBoardMove::new("g2g4").unwrap();
+        assert_eq!(game.is_legal(&g4), true);
+        assert_eq!(game.san(&g4), "g4");
+        game.push(&g4).unwrap();
+        assert_eq!(game.push_san("Ke2").is_ok(), false);
+        assert_eq!(game.turn(), "black");
+        assert_eq!(game.push_san("Qh4#").unwrap().to_square(), "h4");
+        assert_eq!(game.is_check() && game.is_checkmate() && game.is_game_over(), true);
+        assert_eq!(game.result(), "0-1");
+        assert_eq!(game.san_history(), vec!["f3", "e5", "g4", "Qh4#"]);
+        assert_eq!(game.move_stack().len(), 4);
+        assert_eq!(game.pop().unwrap().uci(), "d8h4");
+        assert_eq!(game.is_checkmate(), false);
+
+        // Make sure positions can be loaded and promotions are kept
+        let mut game = PyGame::new(Some("4k3/P7/8/8/8/8/8/4K3 w - - 0 1")).unwrap();
+        assert_eq!(game.push_san("a8=Q+").unwrap().promotion(), Some("q".to_string()));
+        assert_eq!(game.fen(), "Q3k3/8/8/8/8/8/8/4K3 b - - 0 1");
+        assert_eq!(PyGame::new(Some("8/8 w")).is_ok(), false);
+        assert_eq!(PyBoardMove::new("e9e4").is_ok(), false);
+        assert_eq!(game.pgn().contains("a8=Q+"), true);
+    }
 }